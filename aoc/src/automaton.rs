@@ -0,0 +1,289 @@
+/// The cells a rule gets to look at, as `(dx, dy)` offsets in the order the rule receives them.
+#[derive(Clone, Debug)]
+pub enum Neighbourhood {
+    /// The four orthogonal neighbours: up, left, right, down.
+    VonNeumann,
+    /// All eight surrounding cells in reading order, without the cell itself.
+    Moore,
+    Offsets(Vec<(isize, isize)>),
+}
+
+impl Neighbourhood {
+    fn offsets(&self) -> Vec<(isize, isize)> {
+        match self {
+            Neighbourhood::VonNeumann => vec![(0, -1), (-1, 0), (1, 0), (0, 1)],
+            Neighbourhood::Moore => vec![
+                (-1, -1),
+                (0, -1),
+                (1, -1),
+                (-1, 0),
+                (1, 0),
+                (-1, 1),
+                (0, 1),
+                (1, 1),
+            ],
+            Neighbourhood::Offsets(offsets) => offsets.clone(),
+        }
+    }
+}
+
+/// What lies beyond the edge of the grid.
+#[derive(Clone, Debug)]
+pub enum Boundary<T> {
+    /// A fixed value for every neighbour outside the grid, so rules still receive one value per
+    /// offset.
+    Bounded(T),
+    /// The opposite edge of the grid.
+    Wrapping,
+    /// An infinite plane of the given background value, which evolves like any other cell. The
+    /// grid grows every step to fit the cells that can differ from the background.
+    Infinite(T),
+}
+
+/// A cellular automaton on a grid, cells are addressed as `(x, y)` with `x` the column.
+#[derive(Clone, Debug)]
+pub struct Automaton<T> {
+    width: usize,
+    height: usize,
+    // Coordinates of the top left cell, these only move for infinite boundaries.
+    origin: (isize, isize),
+    cells: Vec<T>,
+    offsets: Vec<(isize, isize)>,
+    boundary: Boundary<T>,
+}
+
+impl<T: Clone + PartialEq> Automaton<T> {
+    pub fn new(rows: Vec<Vec<T>>, neighbourhood: Neighbourhood, boundary: Boundary<T>) -> Self {
+        let height = rows.len();
+        let width = rows.first().map_or(0, |r| r.len());
+        assert!(
+            rows.iter().all(|r| r.len() == width),
+            "rows differ in width"
+        );
+        Automaton {
+            width,
+            height,
+            origin: (0, 0),
+            cells: rows.into_iter().flatten().collect(),
+            offsets: neighbourhood.offsets(),
+            boundary,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The value at `(x, y)`, which is the background outside an infinite grid.
+    pub fn get(&self, x: isize, y: isize) -> Option<&T> {
+        let column = x - self.origin.0;
+        let row = y - self.origin.1;
+        if column >= 0 && row >= 0 && column < self.width as isize && row < self.height as isize {
+            return Some(&self.cells[row as usize * self.width + column as usize]);
+        }
+        match &self.boundary {
+            Boundary::Infinite(background) => Some(background),
+            _ => None,
+        }
+    }
+
+    /// Every stored cell with its coordinates, row by row.
+    pub fn cells(&self) -> impl Iterator<Item = ((isize, isize), &T)> {
+        let width = self.width;
+        let origin = self.origin;
+        self.cells.iter().enumerate().map(move |(i, c)| {
+            (
+                (
+                    origin.0 + (i % width) as isize,
+                    origin.1 + (i / width) as isize,
+                ),
+                c,
+            )
+        })
+    }
+
+    /// The stored cells as rows.
+    pub fn rows(&self) -> Vec<Vec<T>> {
+        self.cells
+            .chunks(self.width.max(1))
+            .map(|r| r.to_vec())
+            .collect()
+    }
+
+    pub fn count<P: Fn(&T) -> bool>(&self, predicate: P) -> usize {
+        self.cells.iter().filter(|c| predicate(c)).count()
+    }
+
+    fn neighbours(&self, x: isize, y: isize, neighbours: &mut Vec<T>) {
+        neighbours.clear();
+        // An empty grid has no cells to wrap around to.
+        if self.cells.is_empty() {
+            return;
+        }
+        for (dx, dy) in &self.offsets {
+            let (nx, ny) = (x + dx, y + dy);
+            match &self.boundary {
+                Boundary::Bounded(outside) => {
+                    neighbours.push(self.get(nx, ny).unwrap_or(outside).clone());
+                }
+                Boundary::Wrapping => {
+                    let column = (nx - self.origin.0).rem_euclid(self.width as isize);
+                    let row = (ny - self.origin.1).rem_euclid(self.height as isize);
+                    neighbours
+                        .push(self.cells[row as usize * self.width + column as usize].clone());
+                }
+                Boundary::Infinite(_) => neighbours.push(self.get(nx, ny).unwrap().clone()),
+            }
+        }
+    }
+
+    /// Apply `rule` to every cell at once. The rule receives the cell and its neighbours, and
+    /// returns the new value of the cell. Returns the number of cells that changed, or
+    /// `usize::MAX` if an infinite background changed.
+    pub fn step<F: Fn(&T, &[T]) -> T>(&mut self, rule: F) -> usize {
+        let grow = match self.boundary {
+            Boundary::Infinite(_) => self
+                .offsets
+                .iter()
+                .map(|(dx, dy)| dx.abs().max(dy.abs()))
+                .max()
+                .unwrap_or(0),
+            _ => 0,
+        };
+        let width = self.width + 2 * grow as usize;
+        let height = self.height + 2 * grow as usize;
+        let origin = (self.origin.0 - grow, self.origin.1 - grow);
+
+        let mut changed = 0;
+        let mut cells = Vec::with_capacity(width * height);
+        let mut neighbours = Vec::with_capacity(self.offsets.len());
+        for row in 0..height as isize {
+            for column in 0..width as isize {
+                let (x, y) = (origin.0 + column, origin.1 + row);
+                let current = self.get(x, y).unwrap();
+                self.neighbours(x, y, &mut neighbours);
+                let next = rule(current, &neighbours);
+                if &next != current {
+                    changed += 1;
+                }
+                cells.push(next);
+            }
+        }
+
+        if let Boundary::Infinite(background) = &self.boundary {
+            let next = rule(background, &vec![background.clone(); self.offsets.len()]);
+            if &next != background {
+                changed = usize::MAX;
+            }
+            self.boundary = Boundary::Infinite(next);
+        }
+        self.width = width;
+        self.height = height;
+        self.origin = origin;
+        self.cells = cells;
+        changed
+    }
+
+    /// An iterator that steps the automaton with `rule`, yielding the number of changed cells
+    /// and ending on the first step in which nothing changed.
+    pub fn steps<F: Fn(&T, &[T]) -> T>(&mut self, rule: F) -> Steps<'_, T, F> {
+        Steps {
+            automaton: self,
+            rule,
+        }
+    }
+
+    /// Step until nothing changes, returns the number of that last step.
+    pub fn run_to_fixpoint<F: Fn(&T, &[T]) -> T>(&mut self, rule: F) -> usize {
+        self.steps(rule).count() + 1
+    }
+}
+
+pub struct Steps<'a, T, F> {
+    automaton: &'a mut Automaton<T>,
+    rule: F,
+}
+
+impl<'a, T: Clone + PartialEq, F: Fn(&T, &[T]) -> T> Iterator for Steps<'a, T, F> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        match self.automaton.step(&self.rule) {
+            0 => None,
+            changed => Some(changed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::automaton::{Automaton, Boundary, Neighbourhood};
+
+    fn life(cell: &bool, neighbours: &[bool]) -> bool {
+        let alive = neighbours.iter().filter(|n| **n).count();
+        alive == 3 || (*cell && alive == 2)
+    }
+
+    #[test]
+    fn blinker_on_an_infinite_plane() {
+        let mut automaton = Automaton::new(
+            vec![vec![false, false, false], vec![true, true, true]],
+            Neighbourhood::Moore,
+            Boundary::Infinite(false),
+        );
+        assert_eq!(automaton.step(life), 4);
+        assert_eq!(automaton.count(|c| *c), 3);
+        assert_eq!(automaton.get(1, 0), Some(&true));
+        assert_eq!(automaton.get(1, 2), Some(&true));
+        assert_eq!(automaton.get(-10, 0), Some(&false));
+    }
+
+    #[test]
+    fn fixpoints() {
+        // A block is stable, in a bounded grid a blinker on the edge dies out.
+        let block = vec![vec![true, true], vec![true, true]];
+        let mut automaton = Automaton::new(block, Neighbourhood::Moore, Boundary::Bounded(false));
+        assert_eq!(automaton.run_to_fixpoint(life), 1);
+
+        let line = vec![vec![true, true, true], vec![false, false, false]];
+        let mut automaton = Automaton::new(line, Neighbourhood::Moore, Boundary::Bounded(false));
+        assert_eq!(automaton.steps(life).collect::<Vec<usize>>(), vec![3, 2]);
+        assert_eq!(automaton.count(|c| *c), 0);
+    }
+
+    #[test]
+    fn wrapping_shift() {
+        let row = vec![vec![1, 2, 3]];
+        let mut automaton = Automaton::new(
+            row,
+            Neighbourhood::Offsets(vec![(-1, 0)]),
+            Boundary::Wrapping,
+        );
+        automaton.step(|_, n| n[0]);
+        assert_eq!(automaton.rows(), vec![vec![3, 1, 2]]);
+
+        let mut empty = Automaton::new(
+            vec![vec![], vec![]],
+            Neighbourhood::Moore,
+            Boundary::Wrapping,
+        );
+        assert_eq!(empty.step(|c: &u8, _| *c), 0);
+    }
+
+    #[test]
+    fn bounded_neighbours_line_up() {
+        // The neighbour to the right of the last cell is the outside value, not missing.
+        let row = vec![vec![1, 2, 3]];
+        let mut automaton = Automaton::new(
+            row,
+            Neighbourhood::Offsets(vec![(1, 0), (-1, 0)]),
+            Boundary::Bounded(0),
+        );
+        automaton.step(|_, n| n[0] * 10 + n[1]);
+        assert_eq!(automaton.rows(), vec![vec![20, 31, 2]]);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::matrix::Ring;
+use std::fmt;
+use std::ops::Add;
+use std::ops::Mul;
+
+/// An unsigned integer of any size, for exact results that would overflow any fixed-width type.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct BigUint {
+    // Base 10^9 digits, least significant first, without leading zeros.
+    digits: Vec<u32>,
+}
+
+const BASE: u64 = 1_000_000_000;
+
+impl BigUint {
+    pub fn new(mut value: u64) -> BigUint {
+        let mut digits = Vec::new();
+        while value > 0 {
+            digits.push((value % BASE) as u32);
+            value /= BASE;
+        }
+        BigUint { digits }
+    }
+}
+
+impl Add for BigUint {
+    type Output = BigUint;
+
+    fn add(self, other: BigUint) -> BigUint {
+        let mut digits = Vec::with_capacity(self.digits.len().max(other.digits.len()) + 1);
+        let mut carry = 0;
+        for i in 0..self.digits.len().max(other.digits.len()) {
+            let sum = carry
+                + *self.digits.get(i).unwrap_or(&0) as u64
+                + *other.digits.get(i).unwrap_or(&0) as u64;
+            digits.push((sum % BASE) as u32);
+            carry = sum / BASE;
+        }
+        if carry > 0 {
+            digits.push(carry as u32);
+        }
+        BigUint { digits }
+    }
+}
+
+impl Mul for BigUint {
+    type Output = BigUint;
+
+    fn mul(self, other: BigUint) -> BigUint {
+        if self.digits.is_empty() || other.digits.is_empty() {
+            return BigUint::default();
+        }
+        let mut digits = vec![0u64; self.digits.len() + other.digits.len()];
+        for (i, a) in self.digits.iter().enumerate() {
+            let mut carry = 0;
+            for (j, b) in other.digits.iter().enumerate() {
+                let product = digits[i + j] + *a as u64 * *b as u64 + carry;
+                digits[i + j] = product % BASE;
+                carry = product / BASE;
+            }
+            digits[i + other.digits.len()] += carry;
+        }
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        BigUint {
+            digits: digits.into_iter().map(|d| d as u32).collect(),
+        }
+    }
+}
+
+impl Ring for BigUint {
+    fn zero() -> Self {
+        BigUint::default()
+    }
+
+    fn one() -> Self {
+        BigUint::new(1)
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.digits.split_last() {
+            None => write!(f, "0"),
+            Some((last, rest)) => {
+                write!(f, "{}", last)?;
+                for digit in rest.iter().rev() {
+                    write!(f, "{:09}", digit)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::big_uint::BigUint;
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(BigUint::new(0).to_string(), "0");
+        assert_eq!(
+            (BigUint::new(999_999_999) + BigUint::new(1)).to_string(),
+            "1000000000"
+        );
+        assert_eq!(
+            (BigUint::new(999_999_999_999) * BigUint::new(1_000_000_001)).to_string(),
+            "1000000000998999999999"
+        );
+        assert_eq!(BigUint::new(12) * BigUint::new(0), BigUint::new(0));
+    }
+}
@@ -0,0 +1,133 @@
+use std::cmp::Reverse;
+use std::collections::hash_map;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::iter::FromIterator;
+
+/// A multiset that keeps a count per key, keys are added on first use.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Counter<K: Eq + Hash> {
+    counts: HashMap<K, usize>,
+}
+
+impl<K: Eq + Hash> Default for Counter<K> {
+    fn default() -> Self {
+        Counter {
+            counts: HashMap::new(),
+        }
+    }
+}
+
+impl<K: Eq + Hash> Counter<K> {
+    pub fn new() -> Counter<K> {
+        Counter::default()
+    }
+
+    pub fn add(&mut self, key: K, n: usize) {
+        *self.counts.entry(key).or_insert(0) += n;
+    }
+
+    pub fn get(&self, key: &K) -> usize {
+        *self.counts.get(key).unwrap_or(&0)
+    }
+
+    pub fn merge(&mut self, other: Counter<K>) {
+        for (key, n) in other {
+            self.add(key, n);
+        }
+    }
+
+    /// The sum of all counts.
+    pub fn total(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    /// The number of distinct keys.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, usize)> {
+        self.counts.iter().map(|(k, n)| (k, *n))
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = usize> + '_ {
+        self.counts.values().copied()
+    }
+}
+
+// Ties between equal counts go to the smallest key, rather than to whichever key the hash map
+// happens to visit first.
+impl<K: Eq + Hash + Ord> Counter<K> {
+    pub fn max(&self) -> Option<(&K, usize)> {
+        self.iter().max_by_key(|(k, n)| (*n, Reverse(*k)))
+    }
+
+    pub fn min(&self) -> Option<(&K, usize)> {
+        self.iter().min_by_key(|(k, n)| (*n, *k))
+    }
+
+    /// All keys ordered from highest to lowest count, and by key within a count.
+    pub fn most_common(&self) -> Vec<(&K, usize)> {
+        let mut pairs = self.iter().collect::<Vec<(&K, usize)>>();
+        pairs.sort_by_key(|(k, n)| (Reverse(*n), *k));
+        pairs
+    }
+}
+
+impl<K: Eq + Hash> FromIterator<K> for Counter<K> {
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        let mut counter = Counter::new();
+        for key in iter {
+            counter.add(key, 1);
+        }
+        counter
+    }
+}
+
+impl<K: Eq + Hash> Extend<(K, usize)> for Counter<K> {
+    fn extend<I: IntoIterator<Item = (K, usize)>>(&mut self, iter: I) {
+        for (key, n) in iter {
+            self.add(key, n);
+        }
+    }
+}
+
+impl<K: Eq + Hash> IntoIterator for Counter<K> {
+    type Item = (K, usize);
+    type IntoIter = hash_map::IntoIter<K, usize>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.counts.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::counter::Counter;
+
+    #[test]
+    fn counting() {
+        let mut counter: Counter<char> = "NNCB".chars().collect();
+        counter.add('B', 3);
+        counter.merge(vec!['H'].into_iter().collect());
+
+        assert_eq!(counter.get(&'B'), 4);
+        assert_eq!(counter.get(&'X'), 0);
+        assert_eq!(counter.total(), 8);
+        assert_eq!(counter.max(), Some((&'B', 4)));
+        assert_eq!(counter.most_common()[1], (&'N', 2));
+    }
+
+    #[test]
+    fn ties_go_to_the_smallest_key() {
+        let counter: Counter<char> = "cabbac".chars().collect();
+        assert_eq!(counter.max(), Some((&'a', 2)));
+        assert_eq!(counter.min(), Some((&'a', 2)));
+        assert_eq!(counter.most_common(), vec![(&'a', 2), (&'b', 2), (&'c', 2)]);
+    }
+}
@@ -0,0 +1,334 @@
+use crate::random::Rng;
+use crate::validate::Diagnostic;
+use std::any::Any;
+use std::cell::Cell;
+use std::panic;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::sync::Once;
+use std::thread;
+use std::time::Duration;
+
+/// How long a single input may take before it counts as a hang.
+pub const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Characters that mean something to at least one of the parsers.
+const SPECIAL: &str = "[](){}<>,-|=.#:+ \n0123456789";
+
+/// Why a property failed on an input.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Failure {
+    Panic(String),
+    Timeout,
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "unknown panic".to_string(),
+        },
+    }
+}
+
+thread_local! {
+    // Set on workers, whose panics are expected and would otherwise flood the output.
+    static QUIET: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Wrap the panic hook, once, so that it stays silent on workers and reports panics elsewhere.
+fn install_quiet_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !QUIET.with(|quiet| quiet.get()) {
+                hook(info);
+            }
+        }));
+    });
+}
+
+type Property = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// A thread that runs a property on one input at a time, so that panics are caught and hangs are
+/// cut off. A worker that hangs is left to run in the background and replaced by a new one, a
+/// stack overflow still aborts the process.
+struct Worker {
+    property: Property,
+    inputs: mpsc::Sender<String>,
+    results: mpsc::Receiver<Result<(), String>>,
+}
+
+impl Worker {
+    fn new(property: Property) -> Worker {
+        install_quiet_hook();
+        let (inputs, receiver) = mpsc::channel::<String>();
+        let (sender, results) = mpsc::channel();
+        let worker_property = property.clone();
+        thread::spawn(move || {
+            QUIET.with(|quiet| quiet.set(true));
+            for input in receiver {
+                let result =
+                    panic::catch_unwind(panic::AssertUnwindSafe(|| worker_property(&input)));
+                if sender.send(result.map_err(panic_message)).is_err() {
+                    break;
+                }
+            }
+        });
+        Worker {
+            property,
+            inputs,
+            results,
+        }
+    }
+
+    fn run(&mut self, input: &str, timeout: Duration) -> Result<(), Failure> {
+        self.inputs
+            .send(input.to_string())
+            .expect("the worker stopped");
+        match self.results.recv_timeout(timeout) {
+            Ok(result) => result.map_err(Failure::Panic),
+            Err(_) => {
+                *self = Worker::new(self.property.clone());
+                Err(Failure::Timeout)
+            }
+        }
+    }
+
+    fn shrink(&mut self, input: &str, timeout: Duration) -> String {
+        let mut current = input.chars().collect::<Vec<char>>();
+        let mut chunk = current.len() / 2;
+        while chunk > 0 {
+            let mut shrunk = false;
+            let mut start = 0;
+            while start < current.len() {
+                let end = (start + chunk).min(current.len());
+                let candidate = current[..start]
+                    .iter()
+                    .chain(&current[end..])
+                    .collect::<String>();
+                if let Err(Failure::Panic(_)) = self.run(&candidate, timeout) {
+                    current = candidate.chars().collect();
+                    shrunk = true;
+                } else {
+                    start += chunk;
+                }
+            }
+            if !shrunk {
+                chunk /= 2;
+            }
+        }
+        current.into_iter().collect()
+    }
+}
+
+/// Run `property` on `input` in a worker thread, so that panics are caught and hangs are cut off.
+pub fn run<P: Fn(&str) + Send + Sync + 'static>(
+    property: P,
+    input: &str,
+    timeout: Duration,
+) -> Result<(), Failure> {
+    Worker::new(Arc::new(property)).run(input, timeout)
+}
+
+/// Apply a few random edits to `input`: removing, inserting, replacing or duplicating characters,
+/// or cutting it short.
+pub fn mutate(input: &str, rng: &mut Rng) -> String {
+    let mut chars = input.chars().collect::<Vec<char>>();
+    let alphabet = SPECIAL.chars().chain(input.chars()).collect::<Vec<char>>();
+    for _ in 0..=rng.below(4) {
+        let position = rng.below(chars.len() + 1);
+        match rng.below(6) {
+            0 if position < chars.len() => {
+                chars.remove(position);
+            }
+            1 => chars.insert(position, *rng.choose(&alphabet)),
+            2 if position < chars.len() => chars[position] = *rng.choose(&alphabet),
+            3 if position < chars.len() => {
+                let end = position + 1 + rng.below((chars.len() - position).min(16));
+                let run = chars[position..end].to_vec();
+                chars.splice(position..position, run);
+            }
+            4 if position < chars.len() => {
+                let end = position + 1 + rng.below((chars.len() - position).min(16));
+                chars.drain(position..end);
+            }
+            5 => chars.truncate(position),
+            _ => {}
+        }
+    }
+    chars.into_iter().collect()
+}
+
+/// Remove ever smaller chunks of `input` for as long as `property` keeps panicking on it.
+pub fn shrink<P: Fn(&str) + Send + Sync + 'static>(
+    property: P,
+    input: &str,
+    timeout: Duration,
+) -> String {
+    Worker::new(Arc::new(property)).shrink(input, timeout)
+}
+
+/// Runs a property on known inputs, then on random mutations of them and random strings.
+pub struct Fuzzer {
+    rng: Rng,
+    cases: usize,
+}
+
+impl Fuzzer {
+    pub fn new(seed: u64, cases: usize) -> Fuzzer {
+        Fuzzer {
+            rng: Rng::new(seed),
+            cases,
+        }
+    }
+
+    fn random_input(&mut self, seeds: &[&str]) -> String {
+        if seeds.is_empty() || self.rng.chance(0.1) {
+            let alphabet = SPECIAL
+                .chars()
+                .chain(seeds.iter().flat_map(|s| s.chars()))
+                .collect::<Vec<char>>();
+            let length = self.rng.below(64);
+            (0..length).map(|_| *self.rng.choose(&alphabet)).collect()
+        } else {
+            let seed = *self.rng.choose(seeds);
+            mutate(seed, &mut self.rng)
+        }
+    }
+
+    /// Returns the first input `property` fails on, with panics shrunk to a smaller input.
+    pub fn run<P: Fn(&str) + Send + Sync + 'static>(
+        &mut self,
+        seeds: &[&str],
+        property: P,
+    ) -> Option<(String, Failure)> {
+        let generated = (0..self.cases)
+            .map(|_| self.random_input(seeds))
+            .collect::<Vec<String>>();
+        let inputs = seeds
+            .iter()
+            .copied()
+            .chain(generated.iter().map(|s| s.as_str()));
+        let mut worker = Worker::new(Arc::new(property));
+        for input in inputs {
+            match worker.run(input, TIMEOUT) {
+                Ok(()) => {}
+                Err(Failure::Timeout) => return Some((input.to_string(), Failure::Timeout)),
+                Err(Failure::Panic(_)) => {
+                    let shrunk = worker.shrink(input, TIMEOUT);
+                    let failure = worker.run(&shrunk, TIMEOUT).unwrap_err();
+                    return Some((shrunk, failure));
+                }
+            }
+        }
+        None
+    }
+
+    /// Like `run`, but panics on a failure with the input written as a string literal, so that it
+    /// can be added to the regression cases.
+    pub fn check<P: Fn(&str) + Send + Sync + 'static>(&mut self, seeds: &[&str], property: P) {
+        if let Some((input, failure)) = self.run(seeds, property) {
+            panic!("property failed with {:?} on input {:?}", failure, input);
+        }
+    }
+}
+
+/// Fuzz a day's parser from its examples and the inputs that once broke it: `parses` has to
+/// succeed on every input that `validate` accepts.
+pub fn check_parser(
+    seed: u64,
+    cases: usize,
+    examples: &[&str],
+    regressions: &[&str],
+    validate: fn(&str) -> Result<(), Vec<Diagnostic>>,
+    parses: fn(&str) -> bool,
+) {
+    let seeds = [examples, regressions].concat();
+    Fuzzer::new(seed, cases).check(&seeds, move |input| {
+        assert!(
+            parses(input) || validate(input).is_err(),
+            "valid input was rejected"
+        );
+    });
+}
+
+/// Fuzz a day's solution from its examples and the inputs that once broke it: `solve` validates
+/// its input first, and has to return on every input, with a diagnostic if it cannot answer.
+pub fn check_solver<T: 'static>(
+    seed: u64,
+    cases: usize,
+    examples: &[&str],
+    regressions: &[&str],
+    solve: fn(&str) -> Result<T, Vec<Diagnostic>>,
+) {
+    let seeds = [examples, regressions].concat();
+    Fuzzer::new(seed, cases).check(&seeds, move |input| {
+        let _ = solve(input);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fuzz::{check_parser, check_solver, mutate, run, shrink, Failure, Fuzzer, TIMEOUT};
+    use crate::random::Rng;
+    use crate::validate::Diagnostic;
+
+    fn no_sevens(input: &str) {
+        assert!(!input.contains('7'), "found a seven");
+    }
+
+    #[test]
+    fn finds_and_shrinks_panics() {
+        assert_eq!(run(no_sevens, "123", TIMEOUT), Ok(()));
+        assert_eq!(
+            run(no_sevens, "1273", TIMEOUT),
+            Err(Failure::Panic("found a seven".to_string()))
+        );
+        assert_eq!(shrink(no_sevens, "12[7]3", TIMEOUT), "7");
+
+        let mut rng = Rng::new(1);
+        assert_ne!(mutate("[1,2]", &mut rng), mutate("[1,2]", &mut rng));
+
+        let (input, failure) = Fuzzer::new(1, 1000).run(&["1234"], no_sevens).unwrap();
+        assert_eq!(input, "7");
+        assert_eq!(failure, Failure::Panic("found a seven".to_string()));
+    }
+
+    #[test]
+    fn hangs_time_out() {
+        let hangs = |input: &str| {
+            if input.contains('7') {
+                std::thread::sleep(TIMEOUT * 2);
+            }
+        };
+        let (input, failure) = Fuzzer::new(1, 1000).run(&["1234"], hangs).unwrap();
+        assert!(input.contains('7'));
+        assert_eq!(failure, Failure::Timeout);
+    }
+
+    #[test]
+    #[should_panic(expected = "valid input was rejected")]
+    fn parser_rejects_valid_input() {
+        check_parser(
+            1,
+            100,
+            &["12"],
+            &[],
+            |_| Ok(()),
+            |input| !input.contains('1'),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "attempt to divide by zero")]
+    fn solver_panics_on_valid_input() {
+        fn solve(input: &str) -> Result<usize, Vec<Diagnostic>> {
+            let divisor = input.parse::<usize>().map_err(|_| Vec::new())?;
+            Ok(12 / (divisor % 10))
+        }
+        check_solver(1, 1000, &["12"], &[], solve);
+    }
+}
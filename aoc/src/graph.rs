@@ -0,0 +1,279 @@
+use crate::memo::Memo;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+// Separators tried in order when parsing an edge list, so `a->b` is not read as `a` and `>b`.
+const SEPARATORS: [&str; 4] = ["->", "-", ",", " "];
+
+/// A graph with node names interned to integer ids, ids are handed out in order of appearance.
+#[derive(Clone, Debug, Default)]
+pub struct Graph {
+    directed: bool,
+    names: Vec<String>,
+    ids: HashMap<String, usize>,
+    edges: Vec<Vec<usize>>,
+}
+
+impl Graph {
+    pub fn new(directed: bool) -> Graph {
+        Graph {
+            directed,
+            ..Default::default()
+        }
+    }
+
+    /// Parse one edge per line, written as `a-b`, `a->b`, `a,b` or `a b`. Blank lines are skipped.
+    pub fn parse(input: &str, directed: bool) -> Result<Graph, String> {
+        let mut graph = Graph::new(directed);
+        for (i, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let pair = SEPARATORS
+                .iter()
+                .filter_map(|s| line.split_once(s))
+                .map(|(a, b)| (a.trim(), b.trim()))
+                .find(|(a, b)| !a.is_empty() && !b.is_empty());
+            match pair {
+                Some((a, b)) => {
+                    let a = graph.add_node(a);
+                    let b = graph.add_node(b);
+                    graph.add_edge(a, b);
+                }
+                None => return Err(format!("line {}: '{}' is not an edge", i + 1, line)),
+            }
+        }
+        Ok(graph)
+    }
+
+    /// Get the id of a node, adding it if it is new.
+    pub fn add_node(&mut self, name: &str) -> usize {
+        if let Some(id) = self.ids.get(name) {
+            return *id;
+        }
+        let id = self.names.len();
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), id);
+        self.edges.push(Vec::new());
+        id
+    }
+
+    pub fn add_edge(&mut self, from: usize, to: usize) {
+        if !self.edges[from].contains(&to) {
+            self.edges[from].push(to);
+        }
+        if !self.directed && !self.edges[to].contains(&from) {
+            self.edges[to].push(from);
+        }
+    }
+
+    pub fn id(&self, name: &str) -> Option<usize> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: usize) -> &str {
+        &self.names[id]
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn neighbours(&self, id: usize) -> &[usize] {
+        &self.edges[id]
+    }
+
+    /// Nodes reachable from `start` in depth-first preorder.
+    pub fn dfs(&self, start: usize) -> Vec<usize> {
+        let mut seen = vec![false; self.len()];
+        let mut order = Vec::new();
+        let mut stack = vec![start];
+        while let Some(current) = stack.pop() {
+            if seen[current] {
+                continue;
+            }
+            seen[current] = true;
+            order.push(current);
+            // Push in reverse so neighbours are visited in insertion order.
+            stack.extend(self.edges[current].iter().rev().filter(|n| !seen[**n]));
+        }
+        order
+    }
+
+    /// Nodes reachable from `start` in breadth-first order.
+    pub fn bfs(&self, start: usize) -> Vec<usize> {
+        let mut seen = vec![false; self.len()];
+        let mut order = Vec::new();
+        let mut queue = VecDeque::new();
+        seen[start] = true;
+        queue.push_back(start);
+        while let Some(current) = queue.pop_front() {
+            order.push(current);
+            for next in &self.edges[current] {
+                if !seen[*next] {
+                    seen[*next] = true;
+                    queue.push_back(*next);
+                }
+            }
+        }
+        order
+    }
+
+    /// The connected components, ignoring edge direction.
+    pub fn connected_components(&self) -> Vec<Vec<usize>> {
+        let mut undirected = Graph::new(false);
+        undirected.names = self.names.clone();
+        undirected.edges = vec![Vec::new(); self.len()];
+        for (from, tos) in self.edges.iter().enumerate() {
+            for to in tos {
+                undirected.add_edge(from, *to);
+            }
+        }
+
+        let mut seen = vec![false; self.len()];
+        let mut components = Vec::new();
+        for id in 0..self.len() {
+            if !seen[id] {
+                let component = undirected.dfs(id);
+                component.iter().for_each(|n| seen[*n] = true);
+                components.push(component);
+            }
+        }
+        components
+    }
+
+    /// Every path from `start` to `end`, where `limit` gives the number of times a node may be
+    /// entered. The start node is not entered, so a limit of zero keeps a path from returning to
+    /// it. On top of that, `extra_visits` times per path a node with a non-zero limit may be
+    /// entered once more than its limit allows.
+    pub fn paths<F: Fn(usize) -> usize>(
+        &self,
+        start: usize,
+        end: usize,
+        limit: F,
+        extra_visits: usize,
+    ) -> Vec<Vec<usize>> {
+        let mut paths = Vec::new();
+        let mut visits = vec![0; self.len()];
+        let mut route = vec![start];
+        self.walk(
+            end,
+            &limit,
+            extra_visits,
+            &mut visits,
+            &mut route,
+            &mut |r| paths.push(r.to_vec()),
+        );
+        paths
+    }
+
+    /// The number of paths `paths` would return, without building them. Counts are cached per
+    /// position and visit state, nodes with a limit of `usize::MAX` are not tracked in that state.
+    pub fn count_paths<F: Fn(usize) -> usize>(
+        &self,
+        start: usize,
+        end: usize,
+        limit: F,
+        extra_visits: usize,
+    ) -> usize {
+        let limits = (0..self.len()).map(&limit).collect::<Vec<usize>>();
+        let mut count = Memo::new(
+            |recurse, (current, visits, extra): (usize, Vec<usize>, usize)| -> usize {
+                if current == end {
+                    return 1;
+                }
+
+                let mut paths = 0;
+                for next in &self.edges[current] {
+                    if let Some(remaining_extra) = enter(limits[*next], visits[*next], extra) {
+                        let mut next_visits = visits.clone();
+                        if limits[*next] != usize::MAX {
+                            next_visits[*next] += 1;
+                        }
+                        paths += recurse((*next, next_visits, remaining_extra));
+                    }
+                }
+                paths
+            },
+        );
+        count.get((start, vec![0; self.len()], extra_visits))
+    }
+
+    fn walk<F: Fn(usize) -> usize, G: FnMut(&[usize])>(
+        &self,
+        end: usize,
+        limit: &F,
+        extra_visits: usize,
+        visits: &mut Vec<usize>,
+        route: &mut Vec<usize>,
+        found: &mut G,
+    ) {
+        let current = *route.last().unwrap();
+        if current == end {
+            found(route);
+            return;
+        }
+
+        for next in &self.edges[current] {
+            if let Some(remaining_extra) = enter(limit(*next), visits[*next], extra_visits) {
+                visits[*next] += 1;
+                route.push(*next);
+                self.walk(end, limit, remaining_extra, visits, route, found);
+                route.pop();
+                visits[*next] -= 1;
+            }
+        }
+    }
+}
+
+// Whether a node can be entered, and if so how many extra visits remain afterwards.
+fn enter(limit: usize, visits: usize, extra_visits: usize) -> Option<usize> {
+    if visits < limit {
+        Some(extra_visits)
+    } else if limit > 0 && visits == limit && extra_visits > 0 {
+        Some(extra_visits - 1)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::Graph;
+
+    #[test]
+    fn parse_and_traverse() {
+        let graph = Graph::parse("a-b\nb -> c\n\nd,e\n", true).unwrap();
+        let a = graph.id("a").unwrap();
+
+        assert_eq!(graph.len(), 5);
+        assert_eq!(graph.dfs(a), vec![0, 1, 2]);
+        assert_eq!(graph.bfs(graph.id("c").unwrap()), vec![2]);
+        assert_eq!(
+            graph.connected_components(),
+            vec![vec![0, 1, 2], vec![3, 4]]
+        );
+        assert!(Graph::parse("a-b\nfoo", false).is_err());
+    }
+
+    #[test]
+    fn paths_with_visit_limits() {
+        let graph = Graph::parse("start-A\nstart-b\nA-c\nA-b\nb-d\nA-end\nb-end", false).unwrap();
+        let start = graph.id("start").unwrap();
+        let end = graph.id("end").unwrap();
+        let limit = |id: usize| match graph.name(id) {
+            "start" => 0,
+            "A" => usize::MAX,
+            _ => 1,
+        };
+
+        assert_eq!(graph.paths(start, end, limit, 0).len(), 10);
+        assert_eq!(graph.count_paths(start, end, limit, 1), 36);
+    }
+}
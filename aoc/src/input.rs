@@ -0,0 +1,90 @@
+use crate::read_file;
+use std::fmt;
+use std::str::FromStr;
+
+/// Puzzle input with line endings normalised to `\n` and trailing blank lines removed, so a file
+/// that ends in a newline reads the same as one that does not.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Input {
+    text: String,
+}
+
+impl Input {
+    pub fn new(text: &str) -> Input {
+        Input {
+            text: text.replace("\r\n", "\n").trim_end().to_string(),
+        }
+    }
+
+    pub fn from_day(day: &str) -> Input {
+        Input::new(&read_file(day.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Lines with surrounding whitespace removed. Blank lines inside the input are kept.
+    pub fn lines(&self) -> Vec<&str> {
+        if self.text.is_empty() {
+            return Vec::new();
+        }
+        self.text.split('\n').map(|l| l.trim()).collect()
+    }
+
+    /// Blocks of lines separated by one or more blank lines.
+    pub fn sections(&self) -> Vec<Input> {
+        let mut sections = Vec::new();
+        let mut current: Vec<&str> = Vec::new();
+        for line in self.lines() {
+            if line.is_empty() {
+                if !current.is_empty() {
+                    sections.push(Input::new(&current.join("\n")));
+                    current.clear();
+                }
+            } else {
+                current.push(line);
+            }
+        }
+        if !current.is_empty() {
+            sections.push(Input::new(&current.join("\n")));
+        }
+        sections
+    }
+
+    /// Parse every non-blank line.
+    pub fn parse_lines<T: FromStr>(&self) -> Result<Vec<T>, T::Err> {
+        self.lines()
+            .iter()
+            .filter(|l| !l.is_empty())
+            .map(|l| l.parse())
+            .collect()
+    }
+
+    /// Parse every section as a whole.
+    pub fn parse_sections<T: FromStr>(&self) -> Result<Vec<T>, T::Err> {
+        self.sections().iter().map(|s| s.as_str().parse()).collect()
+    }
+}
+
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input::Input;
+
+    #[test]
+    fn trailing_newlines_are_ignored() {
+        let input = Input::new("1\r\n2\n\n\n  3 \n\n4\n");
+
+        assert_eq!(input.lines(), vec!["1", "2", "", "", "3", "", "4"]);
+        assert_eq!(input.sections().len(), 3);
+        assert_eq!(input.sections()[0], Input::new("1\n2"));
+        assert_eq!(input.parse_lines::<usize>(), Ok(vec![1, 2, 3, 4]));
+        assert!(Input::new("\n").lines().is_empty());
+    }
+}
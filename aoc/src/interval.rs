@@ -0,0 +1,280 @@
+use std::cmp::max;
+use std::cmp::min;
+use std::iter::FromIterator;
+
+/// A half-open interval `[start, end)` of integers. Use `Interval::inclusive` for puzzle ranges
+/// such as `x=10..12`, which include both ends.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Interval {
+    pub start: isize,
+    pub end: isize,
+}
+
+impl Interval {
+    pub fn new(start: isize, end: isize) -> Interval {
+        Interval { start, end }
+    }
+
+    /// The interval `[first, last]`. Panics if `last` is `isize::MAX`, whose end does not fit.
+    pub fn inclusive(first: isize, last: isize) -> Interval {
+        Interval::checked_inclusive(first, last).expect("inclusive interval ends at isize::MAX")
+    }
+
+    /// The interval `[first, last]`, or `None` if `last` is `isize::MAX`.
+    pub fn checked_inclusive(first: isize, last: isize) -> Option<Interval> {
+        Some(Interval::new(first, last.checked_add(1)?))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+
+    pub fn len(&self) -> usize {
+        if self.is_empty() {
+            0
+        } else {
+            // The distance between any two isizes fits in a usize even when the subtraction wraps.
+            self.end.wrapping_sub(self.start) as usize
+        }
+    }
+
+    pub fn contains(&self, value: isize) -> bool {
+        self.start <= value && value < self.end
+    }
+
+    pub fn overlaps(&self, other: &Interval) -> bool {
+        !self.intersection(other).is_empty()
+    }
+
+    pub fn intersection(&self, other: &Interval) -> Interval {
+        Interval::new(max(self.start, other.start), min(self.end, other.end))
+    }
+
+    /// The parts of `self` that are not in `other`, at most two non-empty intervals.
+    pub fn difference(&self, other: &Interval) -> Vec<Interval> {
+        let joint = self.intersection(other);
+        if joint.is_empty() {
+            return if self.is_empty() { vec![] } else { vec![*self] };
+        }
+
+        vec![
+            Interval::new(self.start, joint.start),
+            Interval::new(joint.end, self.end),
+        ]
+        .into_iter()
+        .filter(|i| !i.is_empty())
+        .collect()
+    }
+}
+
+/// A set of integers stored as sorted, disjoint and non-adjacent intervals.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct IntervalSet {
+    intervals: Vec<Interval>,
+}
+
+impl IntervalSet {
+    pub fn new() -> IntervalSet {
+        IntervalSet::default()
+    }
+
+    pub fn insert(&mut self, interval: Interval) {
+        if interval.is_empty() {
+            return;
+        }
+
+        // Swallow every interval that overlaps or touches the new one.
+        let mut merged = interval;
+        let mut intervals = Vec::with_capacity(self.intervals.len() + 1);
+        for existing in &self.intervals {
+            if existing.end < merged.start || merged.end < existing.start {
+                intervals.push(*existing);
+            } else {
+                merged = Interval::new(
+                    min(merged.start, existing.start),
+                    max(merged.end, existing.end),
+                );
+            }
+        }
+        intervals.push(merged);
+        intervals.sort_by_key(|i| i.start);
+        self.intervals = intervals;
+    }
+
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        let mut set = self.clone();
+        for interval in &other.intervals {
+            set.insert(*interval);
+        }
+        set
+    }
+
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut intervals = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.intervals.len() && j < other.intervals.len() {
+            let a = self.intervals[i];
+            let b = other.intervals[j];
+            let joint = a.intersection(&b);
+            if !joint.is_empty() {
+                intervals.push(joint);
+            }
+
+            // Advance whichever interval ends first.
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        IntervalSet { intervals }
+    }
+
+    pub fn difference(&self, other: &IntervalSet) -> IntervalSet {
+        let mut intervals = self.intervals.clone();
+        for removed in &other.intervals {
+            intervals = intervals
+                .iter()
+                .flat_map(|i| i.difference(removed))
+                .collect();
+        }
+        IntervalSet { intervals }
+    }
+
+    pub fn contains(&self, value: isize) -> bool {
+        self.intervals.iter().any(|i| i.contains(value))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// The number of integers in the set.
+    pub fn len(&self) -> usize {
+        self.intervals.iter().map(|i| i.len()).sum()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Interval> {
+        self.intervals.iter()
+    }
+}
+
+impl FromIterator<Interval> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = Interval>>(iter: I) -> Self {
+        let mut set = IntervalSet::new();
+        for interval in iter {
+            set.insert(interval);
+        }
+        set
+    }
+}
+
+/// An axis-aligned region in `N` dimensions, the product of one interval per axis.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Region<const N: usize> {
+    pub axes: [Interval; N],
+}
+
+impl<const N: usize> Region<N> {
+    pub fn new(axes: [Interval; N]) -> Region<N> {
+        Region { axes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.axes.iter().any(|a| a.is_empty())
+    }
+
+    pub fn volume(&self) -> usize {
+        self.axes.iter().map(|a| a.len()).product()
+    }
+
+    pub fn overlaps(&self, other: &Region<N>) -> bool {
+        !self.intersection(other).is_empty()
+    }
+
+    pub fn intersection(&self, other: &Region<N>) -> Region<N> {
+        let mut axes = self.axes;
+        for (axis, other_axis) in axes.iter_mut().zip(other.axes.iter()) {
+            *axis = axis.intersection(other_axis);
+        }
+        Region { axes }
+    }
+
+    /// Subtract `other` from `self`, yielding at most `2 * N` disjoint regions.
+    pub fn difference(&self, other: &Region<N>) -> Vec<Region<N>> {
+        let joint = self.intersection(other);
+        if joint.is_empty() {
+            return if self.is_empty() { vec![] } else { vec![*self] };
+        }
+
+        // Peel off the slabs before and after the joint region one axis at a time, then narrow the
+        // remainder to the joint region along that axis.
+        let mut output = Vec::new();
+        let mut remainder = *self;
+        for axis in 0..N {
+            for slab in remainder.axes[axis].difference(&joint.axes[axis]) {
+                let mut piece = remainder;
+                piece.axes[axis] = slab;
+                output.push(piece);
+            }
+            remainder.axes[axis] = joint.axes[axis];
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interval::{Interval, IntervalSet, Region};
+
+    #[test]
+    fn interval_difference() {
+        let interval = Interval::inclusive(0, 9);
+        assert_eq!(
+            interval.difference(&Interval::inclusive(3, 5)),
+            vec![Interval::new(0, 3), Interval::new(6, 10)]
+        );
+        assert_eq!(interval.difference(&Interval::new(-5, 20)), vec![]);
+        assert_eq!(interval.difference(&Interval::new(20, 30)), vec![interval]);
+    }
+
+    #[test]
+    fn interval_extremes() {
+        assert_eq!(Interval::checked_inclusive(0, isize::MAX), None);
+        assert_eq!(
+            Interval::checked_inclusive(isize::MIN, isize::MAX - 1).map(|i| i.len()),
+            Some(usize::MAX)
+        );
+    }
+
+    #[test]
+    fn interval_set_operations() {
+        let a: IntervalSet = vec![
+            Interval::new(0, 5),
+            Interval::new(5, 8),
+            Interval::new(10, 12),
+        ]
+        .into_iter()
+        .collect();
+        let b: IntervalSet = vec![Interval::new(3, 11)].into_iter().collect();
+
+        assert_eq!(a.iter().count(), 2);
+        assert_eq!(a.len(), 10);
+        assert_eq!(a.union(&b).len(), 12);
+        assert_eq!(a.intersection(&b).len(), 6);
+        assert_eq!(a.difference(&b).len(), 4);
+        assert!(!a.difference(&b).contains(10));
+        assert!(a.difference(&b).contains(11));
+    }
+
+    #[test]
+    fn region_difference_conserves_volume() {
+        let outer = Region::new([Interval::inclusive(10, 12); 3]);
+        let inner = Region::new([Interval::inclusive(11, 13); 3]);
+        let pieces = outer.difference(&inner);
+
+        assert_eq!(outer.intersection(&inner).volume(), 8);
+        assert_eq!(pieces.iter().map(|b| b.volume()).sum::<usize>(), 27 - 8);
+        assert!(pieces.iter().all(|p| !p.overlaps(&inner)));
+    }
+}
@@ -1,13 +1,33 @@
+pub mod automaton;
+pub mod big_uint;
+pub mod counter;
+pub mod disjoint_set;
+pub mod fuzz;
+pub mod graph;
+pub mod input;
+pub mod interval;
+pub mod matrix;
+pub mod memo;
+pub mod number_theory;
+pub mod random;
+pub mod render;
+pub mod rotation;
+pub mod validate;
+
 use core::cmp::max;
 use core::cmp::min;
+use lazy_static::lazy_static;
 use regex::Regex;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fs;
 
+lazy_static! {
+    static ref INT: Regex = Regex::new(r"([-+]?\d+)\D?").unwrap();
+}
+
 pub fn ints_from_str(input: &String) -> Vec<isize> {
-    let re = Regex::new(r"([-+]?\d+)\D?").unwrap();
-    re.captures_iter(input)
+    INT.captures_iter(input)
         .map(|c| c[1].parse().expect("Something went wrong parsing an int"))
         .collect()
 }
@@ -34,9 +54,8 @@ pub fn parse_chars(day: String) -> Vec<char> {
 }
 
 pub fn parse_ints(day: String) -> Vec<isize> {
-    let re = Regex::new(r"([-+]?\d+)\D?").unwrap();
     let input = read_file(day);
-    re.captures_iter(&input)
+    INT.captures_iter(&input)
         .map(|c| c[1].parse().expect("Something went wrong parsing an int"))
         .collect()
 }
@@ -48,24 +67,28 @@ pub fn parse_items(day: String, delimiter: String) -> Vec<String> {
         .collect()
 }
 
+/// The lines of the input as they are, only the empty lines a final newline leaves are dropped.
+/// Use `Input` for trimmed lines.
 pub fn parse_lines(day: String) -> Vec<String> {
-    read_file(day).split("\n").map(|i| i.to_string()).collect()
+    let mut lines = read_file(day)
+        .split('\n')
+        .map(|i| i.to_string())
+        .collect::<Vec<String>>();
+    while lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+    lines
 }
 
 pub fn parse_u32_map(day: String) -> HashMap<(i32, i32), u32> {
-    let input = parse_lines(day)
-        .iter()
-        .map(|l| {
-            l.chars()
-                .map(|c| c.to_digit(10).unwrap())
-                .collect::<Vec<u32>>()
-        })
-        .collect::<Vec<Vec<u32>>>();
+    u32_map_from_str(&read_file(day))
+}
 
+pub fn u32_map_from_str(input: &str) -> HashMap<(i32, i32), u32> {
     let mut map = HashMap::new();
-    for (i, l) in input.iter().enumerate() {
-        for (j, v) in l.iter().enumerate() {
-            map.insert((i as i32, j as i32), v.clone());
+    for (i, l) in input::Input::new(input).lines().iter().enumerate() {
+        for (j, c) in l.chars().enumerate() {
+            map.insert((i as i32, j as i32), c.to_digit(10).unwrap());
         }
     }
     map
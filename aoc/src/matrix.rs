@@ -0,0 +1,206 @@
+use std::ops::Add;
+use std::ops::Index;
+use std::ops::IndexMut;
+use std::ops::Mul;
+
+/// The arithmetic a matrix entry needs: addition, multiplication and their identities.
+pub trait Ring: Clone + PartialEq + Add<Output = Self> + Mul<Output = Self> {
+    fn zero() -> Self;
+    fn one() -> Self;
+}
+
+macro_rules! impl_ring {
+    ($($t:ty),*) => {
+        $(impl Ring for $t {
+            fn zero() -> Self {
+                0
+            }
+
+            fn one() -> Self {
+                1
+            }
+        })*
+    };
+}
+
+impl_ring!(usize, isize, u32, i32, u64, i64, u128, i128);
+
+/// An integer modulo `M`, for results that would overflow any fixed-width type. A modulus of 0
+/// is rejected when the program is compiled.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ModInt<const M: u64> {
+    value: u64,
+}
+
+impl<const M: u64> ModInt<M> {
+    const NONZERO: () = assert!(M > 0, "integers modulo 0 do not exist");
+
+    pub fn new(value: u64) -> ModInt<M> {
+        let () = Self::NONZERO;
+        ModInt { value: value % M }
+    }
+
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+}
+
+impl<const M: u64> Add for ModInt<M> {
+    type Output = ModInt<M>;
+
+    fn add(self, other: ModInt<M>) -> ModInt<M> {
+        ModInt {
+            value: ((self.value as u128 + other.value as u128) % M as u128) as u64,
+        }
+    }
+}
+
+impl<const M: u64> Mul for ModInt<M> {
+    type Output = ModInt<M>;
+
+    fn mul(self, other: ModInt<M>) -> ModInt<M> {
+        ModInt {
+            value: ((self.value as u128 * other.value as u128) % M as u128) as u64,
+        }
+    }
+}
+
+impl<const M: u64> Ring for ModInt<M> {
+    fn zero() -> Self {
+        ModInt::new(0)
+    }
+
+    fn one() -> Self {
+        ModInt::new(1)
+    }
+}
+
+/// A square matrix whose size is fixed when it is created, indexed by `[(row, column)]`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Matrix<T> {
+    size: usize,
+    data: Vec<T>,
+}
+
+impl<T: Ring> Matrix<T> {
+    pub fn zeros(size: usize) -> Matrix<T> {
+        Matrix {
+            size,
+            data: vec![T::zero(); size * size],
+        }
+    }
+
+    pub fn identity(size: usize) -> Matrix<T> {
+        let mut matrix = Matrix::zeros(size);
+        for i in 0..size {
+            matrix[(i, i)] = T::one();
+        }
+        matrix
+    }
+
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Matrix<T> {
+        let size = rows.len();
+        assert!(rows.iter().all(|r| r.len() == size), "matrix is not square");
+        Matrix {
+            size,
+            data: rows.into_iter().flatten().collect(),
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn mul_vector(&self, vector: &[T]) -> Vec<T> {
+        assert_eq!(vector.len(), self.size, "vector has the wrong length");
+        (0..self.size)
+            .map(|i| {
+                (0..self.size).fold(T::zero(), |sum, j| {
+                    sum + self[(i, j)].clone() * vector[j].clone()
+                })
+            })
+            .collect()
+    }
+
+    /// Raise the matrix to `exponent` by repeated squaring, in `O(size^3 log exponent)`.
+    pub fn pow(&self, exponent: u64) -> Matrix<T> {
+        let mut result = Matrix::identity(self.size);
+        let mut base = self.clone();
+        let mut exponent = exponent;
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = &result * &base;
+            }
+            exponent >>= 1;
+            // Skip the last squaring, it is not used and might overflow.
+            if exponent > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+}
+
+impl<T> Index<(usize, usize)> for Matrix<T> {
+    type Output = T;
+
+    fn index(&self, (row, column): (usize, usize)) -> &T {
+        &self.data[row * self.size + column]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Matrix<T> {
+    fn index_mut(&mut self, (row, column): (usize, usize)) -> &mut T {
+        &mut self.data[row * self.size + column]
+    }
+}
+
+impl<T: Ring> Mul for &Matrix<T> {
+    type Output = Matrix<T>;
+
+    fn mul(self, other: &Matrix<T>) -> Matrix<T> {
+        assert_eq!(self.size, other.size, "matrices have different sizes");
+        let mut result: Matrix<T> = Matrix::zeros(self.size);
+        for i in 0..self.size {
+            for k in 0..self.size {
+                let a = &self[(i, k)];
+                if *a == T::zero() {
+                    continue;
+                }
+                for j in 0..self.size {
+                    result[(i, j)] = result[(i, j)].clone() + a.clone() * other[(k, j)].clone();
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::big_uint::BigUint;
+    use crate::matrix::{Matrix, ModInt};
+
+    #[test]
+    fn fibonacci() {
+        let matrix: Matrix<u64> = Matrix::from_rows(vec![vec![1, 1], vec![1, 0]]);
+        assert_eq!(matrix.pow(0), Matrix::identity(2));
+        assert_eq!(matrix.pow(90)[(0, 1)], 2880067194370816120);
+        assert_eq!(matrix.mul_vector(&[1, 0]), vec![1, 1]);
+
+        let modular: Matrix<ModInt<1_000_000_007>> = Matrix::from_rows(vec![
+            vec![ModInt::new(1), ModInt::new(1)],
+            vec![ModInt::new(1), ModInt::new(0)],
+        ]);
+        assert_eq!(modular.pow(1000)[(0, 1)].value(), 517691607);
+
+        let big: Matrix<BigUint> = Matrix::from_rows(vec![
+            vec![BigUint::new(1), BigUint::new(1)],
+            vec![BigUint::new(1), BigUint::new(0)],
+        ]);
+        assert_eq!(
+            big.pow(200)[(0, 1)].to_string(),
+            "280571172992510140037611932413038677189525"
+        );
+    }
+}
@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A cache around a recursive function. The function receives a `recurse` callback to call
+/// itself through the cache, so a recursive solution can be written directly:
+///
+/// ```
+/// use aoc::memo::Memo;
+///
+/// let mut fibonacci = Memo::new(|recurse, n: u64| -> u64 {
+///     if n < 2 {
+///         n
+///     } else {
+///         recurse(n - 1) + recurse(n - 2)
+///     }
+/// });
+/// assert_eq!(fibonacci.get(90), 2880067194370816120);
+/// ```
+pub struct Memo<K, V, F> {
+    cache: HashMap<K, V>,
+    f: F,
+}
+
+impl<K, V, F> Memo<K, V, F>
+where
+    K: Clone + Eq + Hash,
+    V: Clone,
+    F: Fn(&mut dyn FnMut(K) -> V, K) -> V,
+{
+    pub fn new(f: F) -> Memo<K, V, F> {
+        Memo {
+            cache: HashMap::new(),
+            f,
+        }
+    }
+
+    pub fn get(&mut self, key: K) -> V {
+        lookup(&mut self.cache, &self.f, key)
+    }
+
+    /// The number of cached results.
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+}
+
+fn lookup<K, V, F>(cache: &mut HashMap<K, V>, f: &F, key: K) -> V
+where
+    K: Clone + Eq + Hash,
+    V: Clone,
+    F: Fn(&mut dyn FnMut(K) -> V, K) -> V,
+{
+    if let Some(value) = cache.get(&key) {
+        return value.clone();
+    }
+    let value = f(&mut |k| lookup(cache, f, k), key.clone());
+    cache.insert(key, value.clone());
+    value
+}
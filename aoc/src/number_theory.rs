@@ -0,0 +1,150 @@
+use std::convert::TryFrom;
+
+pub fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+pub fn lcm(a: usize, b: usize) -> usize {
+    checked_lcm(a, b).expect("lcm overflowed")
+}
+
+pub fn checked_lcm(a: usize, b: usize) -> Option<usize> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+/// Returns `(g, x, y)` such that `a * x + b * y == g == gcd(a, b)`.
+pub fn extended_gcd(a: isize, b: isize) -> (isize, isize, isize) {
+    if b == 0 {
+        (a.abs(), a.signum(), 0)
+    } else {
+        let (g, x, y) = extended_gcd(b, a.rem_euclid(b));
+        (g, y, x - a.div_euclid(b) * y)
+    }
+}
+
+/// `base^exponent mod modulus`, intermediate products are computed in 128 bits.
+pub fn mod_pow(base: usize, exponent: usize, modulus: usize) -> usize {
+    if modulus == 1 {
+        return 0;
+    }
+    let modulus = modulus as u128;
+    let mut result: u128 = 1;
+    let mut base = base as u128 % modulus;
+    let mut exponent = exponent;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = result * base % modulus;
+        }
+        base = base * base % modulus;
+        exponent >>= 1;
+    }
+    result as usize
+}
+
+/// The inverse of `a` modulo `modulus`, if the modulus is positive and `a` and `modulus` are
+/// coprime.
+pub fn mod_inverse(a: isize, modulus: isize) -> Option<isize> {
+    if modulus <= 0 {
+        return None;
+    }
+    let (g, x, _) = extended_gcd(a.rem_euclid(modulus), modulus);
+    if g == 1 {
+        Some(x.rem_euclid(modulus))
+    } else {
+        None
+    }
+}
+
+/// Solve `x = r_i (mod m_i)` for every `(r_i, m_i)` using the Chinese remainder theorem. Moduli
+/// do not need to be coprime. Returns `(x, m)` with `0 <= x < m` and `m` the lcm of the moduli,
+/// or `None` if the system has no solution or `m` does not fit in an `isize`.
+pub fn crt(congruences: &[(isize, isize)]) -> Option<(isize, isize)> {
+    let mut x: i128 = 0;
+    let mut m: i128 = 1;
+    for (r, n) in congruences {
+        let r = *r as i128;
+        let n = *n as i128;
+
+        // Find k such that x + k * m = r (mod n).
+        let (g, p, _) = extended_gcd(isize::try_from(m).ok()?, n as isize);
+        let g = g as i128;
+        if (r - x) % g != 0 {
+            return None;
+        }
+        let step = n / g;
+        let k = ((r - x) / g % step * (p as i128 % step)).rem_euclid(step);
+        x += k * m;
+        m = m.checked_mul(step)?;
+        x = x.rem_euclid(m);
+    }
+    Some((isize::try_from(x).ok()?, isize::try_from(m).ok()?))
+}
+
+/// The `n`th triangular number, `1 + 2 + ... + n`.
+pub fn triangular(n: usize) -> usize {
+    checked_triangular(n).expect("triangular number overflowed")
+}
+
+pub fn checked_triangular(n: usize) -> Option<usize> {
+    // Divide whichever factor is even to avoid overflowing on the intermediate product.
+    if n.is_multiple_of(2) {
+        (n / 2).checked_mul(n + 1)
+    } else {
+        n.checked_mul(n / 2 + 1)
+    }
+}
+
+/// The largest integer whose square is at most `n`.
+pub fn isqrt(n: usize) -> usize {
+    // Start from the floating point estimate and correct for rounding errors on large inputs.
+    let mut x = (n as f64).sqrt() as usize;
+    while x.checked_mul(x).is_none_or(|s| s > n) {
+        x -= 1;
+    }
+    while (x + 1).checked_mul(x + 1).is_some_and(|s| s <= n) {
+        x += 1;
+    }
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::number_theory::*;
+
+    #[test]
+    fn gcd_and_lcm() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(lcm(4, 6), 12);
+        assert_eq!(checked_lcm(usize::MAX, usize::MAX - 1), None);
+        assert_eq!(extended_gcd(240, 46), (2, -9, 47));
+    }
+
+    #[test]
+    fn modular_arithmetic() {
+        assert_eq!(mod_pow(4, 13, 497), 445);
+        assert_eq!(mod_pow(usize::MAX, 2, usize::MAX - 1), 1);
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(4, 8), None);
+        assert_eq!(mod_inverse(3, 0), None);
+        assert_eq!(mod_inverse(3, -11), None);
+        assert_eq!(crt(&[(0, 3), (3, 4), (4, 5)]), Some((39, 60)));
+        assert_eq!(crt(&[(1, 4), (3, 6)]), Some((9, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+    }
+
+    #[test]
+    fn triangular_and_roots() {
+        assert_eq!(triangular(4), 10);
+        assert_eq!(checked_triangular(usize::MAX), None);
+        assert_eq!(isqrt(15), 3);
+        assert_eq!(isqrt(16), 4);
+        assert_eq!(isqrt(usize::MAX), 4294967295);
+    }
+}
@@ -0,0 +1,77 @@
+use std::ops::RangeInclusive;
+
+/// A small seedable random number generator (SplitMix64), so that anything generated from a seed
+/// can be reproduced.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A uniform value in `0..n`.
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "cannot pick from an empty range");
+        ((self.next_u64() as u128 * n as u128) >> 64) as usize
+    }
+
+    /// A uniform value in the inclusive range.
+    pub fn range(&mut self, range: RangeInclusive<isize>) -> isize {
+        let (start, end) = (*range.start() as i128, *range.end() as i128);
+        assert!(start <= end, "cannot pick from an empty range");
+        let span = (end - start + 1) as u128;
+        (start + ((self.next_u64() as u128 * span) >> 64) as i128) as isize
+    }
+
+    /// True with the given probability.
+    pub fn chance(&mut self, probability: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < probability
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::random::Rng;
+
+    #[test]
+    fn reproducible_and_in_range() {
+        let mut a = Rng::new(2021);
+        let mut b = Rng::new(2021);
+        assert_eq!(
+            (0..10).map(|_| a.next_u64()).collect::<Vec<u64>>(),
+            (0..10).map(|_| b.next_u64()).collect::<Vec<u64>>()
+        );
+
+        let mut rng = Rng::new(7);
+        assert!((0..1000).all(|_| rng.below(6) < 6));
+        assert!((0..1000).all(|_| (-3..=3).contains(&rng.range(-3..=3))));
+        assert_eq!(rng.range(isize::MIN..=isize::MIN), isize::MIN);
+        rng.range(isize::MIN..=isize::MAX);
+
+        let mut items = (0..10).collect::<Vec<usize>>();
+        rng.shuffle(&mut items);
+        items.sort_unstable();
+        assert_eq!(items, (0..10).collect::<Vec<usize>>());
+    }
+}
@@ -0,0 +1,167 @@
+use crate::validate::Diagnostic;
+use std::collections::HashSet;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Lay a set of `(x, y)` points out on a grid spanning their bounding box.
+pub fn points_to_rows(points: &HashSet<(isize, isize)>) -> Vec<Vec<bool>> {
+    if points.is_empty() {
+        return Vec::new();
+    }
+    let min_x = points.iter().map(|t| t.0).min().unwrap();
+    let max_x = points.iter().map(|t| t.0).max().unwrap();
+    let min_y = points.iter().map(|t| t.1).min().unwrap();
+    let max_y = points.iter().map(|t| t.1).max().unwrap();
+
+    (min_y..=max_y)
+        .map(|y| (min_x..=max_x).map(|x| points.contains(&(x, y))).collect())
+        .collect()
+}
+
+pub fn points_to_text(points: &HashSet<(isize, isize)>, on: char, off: char) -> String {
+    grid_to_text(&points_to_rows(points), |p| if *p { on } else { off })
+}
+
+/// One line per row, every cell drawn as the character `draw` picks for it.
+pub fn grid_to_text<T, F: Fn(&T) -> char>(rows: &[Vec<T>], draw: F) -> String {
+    let mut output = String::new();
+    for row in rows {
+        output.extend(row.iter().map(&draw));
+        output.push('\n');
+    }
+    output
+}
+
+/// Write a plain PBM bitmap, where `true` is black.
+pub fn write_pbm<W: Write>(out: &mut W, rows: &[Vec<bool>]) -> io::Result<()> {
+    let width = rows.first().map_or(0, |r| r.len());
+    writeln!(out, "P1\n{} {}", width, rows.len())?;
+    for row in rows {
+        let line = row
+            .iter()
+            .map(|p| if *p { "1" } else { "0" })
+            .collect::<Vec<&str>>()
+            .join(" ");
+        writeln!(out, "{}", line)?;
+    }
+    Ok(())
+}
+
+/// Write a plain PPM image from RGB pixels.
+pub fn write_ppm<W: Write>(out: &mut W, rows: &[Vec<(u8, u8, u8)>]) -> io::Result<()> {
+    let width = rows.first().map_or(0, |r| r.len());
+    writeln!(out, "P3\n{} {}\n255", width, rows.len())?;
+    for row in rows {
+        let line = row
+            .iter()
+            .map(|(r, g, b)| format!("{} {} {}", r, g, b))
+            .collect::<Vec<String>>()
+            .join(" ");
+        writeln!(out, "{}", line)?;
+    }
+    Ok(())
+}
+
+/// Write a plain PGM image from grey values, where 0 is black.
+pub fn write_pgm<W: Write>(out: &mut W, rows: &[Vec<u8>]) -> io::Result<()> {
+    let width = rows.first().map_or(0, |r| r.len());
+    writeln!(out, "P2\n{} {}\n255", width, rows.len())?;
+    for row in rows {
+        let line = row
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<Vec<String>>()
+            .join(" ");
+        writeln!(out, "{}", line)?;
+    }
+    Ok(())
+}
+
+/// The error a renderer returns for an input that does not validate, listing its diagnostics.
+pub fn invalid_input(diagnostics: Vec<Diagnostic>) -> io::Error {
+    let messages = diagnostics
+        .iter()
+        .map(|d| d.to_string())
+        .collect::<Vec<String>>();
+    io::Error::new(io::ErrorKind::InvalidData, messages.join("\n"))
+}
+
+/// Writes numbered frames such as `fold_00000.pbm`, `fold_00001.pbm` to a directory, for
+/// stitching into an animation.
+pub struct FrameWriter {
+    directory: PathBuf,
+    prefix: String,
+    next: usize,
+}
+
+impl FrameWriter {
+    pub fn new(directory: &Path, prefix: &str) -> io::Result<FrameWriter> {
+        fs::create_dir_all(directory)?;
+        Ok(FrameWriter {
+            directory: directory.to_path_buf(),
+            prefix: prefix.to_string(),
+            next: 0,
+        })
+    }
+
+    /// The number of frames written so far.
+    pub fn len(&self) -> usize {
+        self.next
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.next == 0
+    }
+
+    fn create(&mut self, extension: &str) -> io::Result<BufWriter<File>> {
+        let path = self
+            .directory
+            .join(format!("{}_{:05}.{}", self.prefix, self.next, extension));
+        self.next += 1;
+        Ok(BufWriter::new(File::create(path)?))
+    }
+
+    pub fn write_text(&mut self, text: &str) -> io::Result<()> {
+        self.create("txt")?.write_all(text.as_bytes())
+    }
+
+    pub fn write_pbm(&mut self, rows: &[Vec<bool>]) -> io::Result<()> {
+        write_pbm(&mut self.create("pbm")?, rows)
+    }
+
+    pub fn write_pgm(&mut self, rows: &[Vec<u8>]) -> io::Result<()> {
+        write_pgm(&mut self.create("pgm")?, rows)
+    }
+
+    pub fn write_ppm(&mut self, rows: &[Vec<(u8, u8, u8)>]) -> io::Result<()> {
+        write_ppm(&mut self.create("ppm")?, rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::render::{points_to_text, write_pbm, write_pgm};
+    use std::collections::HashSet;
+
+    #[test]
+    fn render_points() {
+        let points: HashSet<(isize, isize)> = vec![(1, 1), (3, 2)].into_iter().collect();
+        assert_eq!(points_to_text(&points, '#', '.'), "#..\n..#\n");
+
+        let mut output = Vec::new();
+        write_pbm(&mut output, &[vec![true, false]]).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "P1\n2 1\n1 0\n");
+
+        let mut output = Vec::new();
+        write_pgm(&mut output, &[vec![0, 128, 255]]).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "P2\n3 1\n255\n0 128 255\n"
+        );
+    }
+}
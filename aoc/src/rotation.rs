@@ -0,0 +1,124 @@
+use std::ops::Mul;
+
+/// A proper rotation of 3D space that maps axes onto axes, stored as an integer matrix.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Rotation {
+    matrix: [[isize; 3]; 3],
+}
+
+impl Rotation {
+    pub fn identity() -> Rotation {
+        Rotation {
+            matrix: [[1, 0, 0], [0, 1, 0], [0, 0, 1]],
+        }
+    }
+
+    /// All 24 orientations: every signed permutation matrix with determinant 1.
+    pub fn all() -> Vec<Rotation> {
+        let permutations = [
+            [0, 1, 2],
+            [0, 2, 1],
+            [1, 0, 2],
+            [1, 2, 0],
+            [2, 0, 1],
+            [2, 1, 0],
+        ];
+
+        let mut rotations = Vec::new();
+        for permutation in &permutations {
+            for signs in 0..8 {
+                let mut matrix = [[0; 3]; 3];
+                for (row, column) in permutation.iter().enumerate() {
+                    matrix[row][*column] = if signs & (1 << row) == 0 { 1 } else { -1 };
+                }
+                let rotation = Rotation { matrix };
+                if rotation.determinant() == 1 {
+                    rotations.push(rotation);
+                }
+            }
+        }
+        rotations
+    }
+
+    pub fn matrix(&self) -> [[isize; 3]; 3] {
+        self.matrix
+    }
+
+    fn determinant(&self) -> isize {
+        let m = &self.matrix;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    /// The rotation that applies `other` first and then `self`.
+    pub fn compose(&self, other: &Rotation) -> Rotation {
+        let mut matrix = [[0; 3]; 3];
+        for (i, row) in matrix.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..3).map(|k| self.matrix[i][k] * other.matrix[k][j]).sum();
+            }
+        }
+        Rotation { matrix }
+    }
+
+    /// Rotation matrices are orthogonal, so the inverse is the transpose.
+    pub fn inverse(&self) -> Rotation {
+        let mut matrix = [[0; 3]; 3];
+        for (i, row) in matrix.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = self.matrix[j][i];
+            }
+        }
+        Rotation { matrix }
+    }
+
+    pub fn apply(&self, point: [isize; 3]) -> [isize; 3] {
+        let mut output = [0; 3];
+        for (i, value) in output.iter_mut().enumerate() {
+            *value = (0..3).map(|k| self.matrix[i][k] * point[k]).sum();
+        }
+        output
+    }
+}
+
+impl Mul for Rotation {
+    type Output = Rotation;
+
+    fn mul(self, other: Rotation) -> Rotation {
+        self.compose(&other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rotation::Rotation;
+    use std::collections::HashSet;
+
+    #[test]
+    fn rotations_form_a_group() {
+        let rotations = Rotation::all();
+        let set = rotations.iter().copied().collect::<HashSet<Rotation>>();
+
+        assert_eq!(rotations.len(), 24);
+        assert_eq!(set.len(), 24);
+        assert!(set.contains(&Rotation::identity()));
+        for a in &rotations {
+            assert_eq!(*a * a.inverse(), Rotation::identity());
+            for b in &rotations {
+                assert!(set.contains(&(*a * *b)));
+            }
+        }
+    }
+
+    #[test]
+    fn compose_applies_right_to_left() {
+        let rotations = Rotation::all();
+        let point = [1, 2, 3];
+        for a in &rotations {
+            for b in &rotations {
+                assert_eq!(a.compose(b).apply(point), a.apply(b.apply(point)));
+            }
+        }
+    }
+}
@@ -0,0 +1,283 @@
+use crate::input::Input;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+/// A problem with the input, on the given 1-based line if it can be pinned to one.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Diagnostic {
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Collects diagnostics while checking an input. Lines are passed as 0-based indices into
+/// `Input::lines`, and reported 1-based.
+#[derive(Clone, Debug, Default)]
+pub struct Validator {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Validator {
+    pub fn new() -> Validator {
+        Validator::default()
+    }
+
+    pub fn error(&mut self, line: Option<usize>, message: &str) {
+        self.diagnostics.push(Diagnostic {
+            line: line.map(|l| l + 1),
+            message: message.to_string(),
+        });
+    }
+
+    /// Report `message` unless `condition` holds, returns the condition.
+    pub fn check(&mut self, condition: bool, line: Option<usize>, message: &str) -> bool {
+        if !condition {
+            self.error(line, message);
+        }
+        condition
+    }
+
+    pub fn non_empty(&mut self, lines: &[&str]) -> bool {
+        self.check(!lines.is_empty(), None, "the input is empty")
+    }
+
+    pub fn line_count(&mut self, lines: &[&str], expected: RangeInclusive<usize>) -> bool {
+        let count = lines.len();
+        let message = if expected.start() == expected.end() {
+            format!("expected {} lines, found {}", expected.start(), count)
+        } else {
+            format!(
+                "expected {} to {} lines, found {}",
+                expected.start(),
+                expected.end(),
+                count
+            )
+        };
+        self.check(expected.contains(&count), None, &message)
+    }
+
+    /// Check that every line is non-empty and as wide as the first, `offset` is the index of the
+    /// first line. Returns the width if the lines form a grid.
+    pub fn grid(&mut self, lines: &[&str], offset: usize) -> Option<usize> {
+        if !self.non_empty(lines) {
+            return None;
+        }
+        let width = lines[0].chars().count();
+        let mut valid = self.check(width > 0, Some(offset), "grid row is empty");
+        for (i, line) in lines.iter().enumerate() {
+            let found = line.chars().count();
+            valid &= self.check(
+                found == width,
+                Some(offset + i),
+                &format!("expected a row of width {}, found {}", width, found),
+            );
+        }
+        if valid {
+            Some(width)
+        } else {
+            None
+        }
+    }
+
+    /// Check that `text` only contains characters from `allowed`.
+    pub fn charset(&mut self, line: usize, text: &str, allowed: &str) -> bool {
+        match text
+            .chars()
+            .enumerate()
+            .find(|(_, c)| !allowed.contains(*c))
+        {
+            Some((column, c)) => {
+                self.error(
+                    Some(line),
+                    &format!("unexpected character '{}' in column {}", c, column + 1),
+                );
+                false
+            }
+            None => true,
+        }
+    }
+
+    pub fn parse<T: FromStr>(&mut self, line: usize, text: &str, what: &str) -> Option<T> {
+        let value = text.parse().ok();
+        if value.is_none() {
+            self.error(Some(line), &format!("'{}' is not a valid {}", text, what));
+        }
+        value
+    }
+
+    pub fn in_range<T: PartialOrd + fmt::Display>(
+        &mut self,
+        line: usize,
+        value: T,
+        range: RangeInclusive<T>,
+        what: &str,
+    ) -> bool {
+        let message = format!(
+            "{} {} is outside {}..={}",
+            what,
+            value,
+            range.start(),
+            range.end()
+        );
+        self.check(range.contains(&value), Some(line), &message)
+    }
+
+    /// The integers in `text`, if there are exactly `count` of them and they all fit.
+    pub fn ints(&mut self, line: usize, text: &str, count: usize) -> Option<Vec<isize>> {
+        lazy_static! {
+            static ref INT: Regex = Regex::new(r"[-+]?\d+").unwrap();
+        }
+        let values = INT
+            .find_iter(text)
+            .map(|m| self.parse(line, m.as_str(), "integer"))
+            .collect::<Vec<Option<isize>>>();
+        if !self.check(
+            values.len() == count,
+            Some(line),
+            &format!("expected {} numbers, found {}", count, values.len()),
+        ) {
+            return None;
+        }
+        values.into_iter().collect()
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    pub fn finish(self) -> Result<(), Vec<Diagnostic>> {
+        if self.diagnostics.is_empty() {
+            Ok(())
+        } else {
+            Err(self.diagnostics)
+        }
+    }
+}
+
+/// Check the lines of `input` with `check`, and report everything it found.
+pub fn check_lines<F: FnOnce(&mut Validator, &[&str])>(
+    input: &str,
+    check: F,
+) -> Result<(), Vec<Diagnostic>> {
+    let input = Input::new(input);
+    let mut validator = Validator::new();
+    check(&mut validator, &input.lines());
+    validator.finish()
+}
+
+/// Check that `input` has lines, and each of them with `check`, which receives its index.
+pub fn check_each_line<F: FnMut(&mut Validator, usize, &str)>(
+    input: &str,
+    mut check: F,
+) -> Result<(), Vec<Diagnostic>> {
+    check_lines(input, |validator, lines| {
+        validator.non_empty(lines);
+        for (i, line) in lines.iter().enumerate() {
+            check(validator, i, line);
+        }
+    })
+}
+
+/// Blocks of lines separated by blank lines, each with the index of its first line.
+pub fn sections<'a>(lines: &[&'a str]) -> Vec<(usize, Vec<&'a str>)> {
+    let mut sections = Vec::new();
+    let mut current: Option<(usize, Vec<&str>)> = None;
+    for (i, line) in lines.iter().enumerate() {
+        if line.is_empty() {
+            sections.extend(current.take());
+        } else {
+            current.get_or_insert((i, Vec::new())).1.push(line);
+        }
+    }
+    sections.extend(current);
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::validate::{check_each_line, check_lines, sections, Diagnostic, Validator};
+
+    #[test]
+    fn diagnostics() {
+        let mut validator = Validator::new();
+        assert_eq!(validator.ints(0, "1,-2 -> 3", 3), Some(vec![1, -2, 3]));
+        assert_eq!(validator.grid(&["#.", "..", "#"], 0), None);
+        assert!(!validator.charset(3, "#x", "#."));
+        assert_eq!(validator.ints(4, "99999999999999999999", 1), None);
+        assert!(validator.in_range(5, 3, 1..=10, "position"));
+
+        assert_eq!(
+            validator.finish().unwrap_err(),
+            vec![
+                Diagnostic {
+                    line: Some(3),
+                    message: "expected a row of width 2, found 1".to_string()
+                },
+                Diagnostic {
+                    line: Some(4),
+                    message: "unexpected character 'x' in column 2".to_string()
+                },
+                Diagnostic {
+                    line: Some(5),
+                    message: "'99999999999999999999' is not a valid integer".to_string()
+                },
+            ]
+        );
+        assert_eq!(
+            sections(&["a", "", "", "b", "c"]),
+            vec![(0, vec!["a"]), (3, vec!["b", "c"])]
+        );
+    }
+
+    #[test]
+    fn line_checks() {
+        let digits = |validator: &mut Validator, i: usize, line: &str| {
+            validator.charset(i, line, "0123456789");
+        };
+        assert_eq!(
+            check_each_line(
+                "12
+34
+
+", digits
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            check_each_line(
+                "12
+3a", digits
+            )
+            .unwrap_err()[0]
+                .line,
+            Some(2)
+        );
+        assert_eq!(
+            check_each_line("", digits).unwrap_err()[0].message,
+            "the input is empty"
+        );
+        assert_eq!(
+            check_lines(
+                "1
+2",
+                |validator, lines| {
+                    validator.line_count(lines, 1..=1);
+                }
+            )
+            .unwrap_err()[0]
+                .message,
+            "expected 1 lines, found 2"
+        );
+    }
+}
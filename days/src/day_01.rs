@@ -1,27 +1,164 @@
-use aoc::parse_ints;
+use aoc::input::Input;
+use aoc::random::Rng;
+use aoc::read_file;
+use aoc::validate::check_each_line;
+use aoc::validate::Diagnostic;
+use std::cmp::Ordering;
+use std::collections::VecDeque;
 
-fn part_a(inputs: &Vec<isize>, window: usize) -> usize {
-    let mut count: usize = 0;
+/// How the sums of a sliding window over a sonar sweep change from one window to the next.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Sweep {
+    pub increases: usize,
+    pub decreases: usize,
+    pub plateaus: usize,
+    /// The largest number of increases in a row.
+    pub longest_increase: usize,
+}
 
-    for i in 1..(inputs.len() + 1 - window) {
-        let mut cum_a = 0;
-        for j in (i - 1)..(i - 1 + window) {
-            cum_a += inputs[j];
+/// Compare the sums of every two consecutive windows of `window` depths in one pass, keeping only
+/// the last `window` depths in memory. Consecutive windows share all but one depth, so their sums
+/// differ by the depth that enters minus the depth that leaves.
+pub fn analyse<I: IntoIterator<Item = isize>>(depths: I, window: usize) -> Sweep {
+    assert!(window > 0, "the window must hold at least one depth");
+    let mut sweep = Sweep::default();
+    let mut buffer = VecDeque::with_capacity(window);
+    let mut run = 0;
+    for depth in depths {
+        if buffer.len() < window {
+            buffer.push_back(depth);
+            continue;
         }
-
-        let mut cum_b = 0;
-        for k in i..(i + window) {
-            cum_b += inputs[k];
+        let leaving = buffer.pop_front().unwrap();
+        buffer.push_back(depth);
+        match depth.cmp(&leaving) {
+            Ordering::Greater => {
+                sweep.increases += 1;
+                run += 1;
+                sweep.longest_increase = sweep.longest_increase.max(run);
+            }
+            Ordering::Less => {
+                sweep.decreases += 1;
+                run = 0;
+            }
+            Ordering::Equal => {
+                sweep.plateaus += 1;
+                run = 0;
+            }
         }
+    }
+    sweep
+}
 
-        if cum_b > cum_a {
-            count += 1;
+/// Check that the report is a list of depths.
+pub fn validate(input: &str) -> Result<(), Vec<Diagnostic>> {
+    check_each_line(input, |validator, i, line| {
+        if let Some(depth) = validator.parse::<isize>(i, line, "depth") {
+            validator.in_range(i, depth, 0..=isize::MAX, "depth");
         }
+    })
+}
+
+/// Generate `size` depths that mostly, but not always, increase.
+pub fn generate(size: usize, seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+    let mut depth: isize = 100;
+    let mut output = String::new();
+    for _ in 0..size {
+        depth = (depth + rng.range(-5..=10)).max(0);
+        output.push_str(&format!("{}\n", depth));
     }
-    count
+    output
+}
+
+/// Parse the depths of a sonar sweep.
+pub fn parse(input: &str) -> Result<Vec<isize>, Vec<Diagnostic>> {
+    validate(input)?;
+    let input = Input::new(input);
+    input
+        .lines()
+        .iter()
+        .enumerate()
+        .map(|(i, l)| {
+            l.parse().map_err(|_| {
+                vec![Diagnostic {
+                    line: Some(i + 1),
+                    message: format!("'{}' is not a depth", l),
+                }]
+            })
+        })
+        .collect()
+}
+
+/// The number of depths that increase, and the number of sums of three depths that increase.
+pub fn solve(input: &str) -> Result<(usize, usize), Vec<Diagnostic>> {
+    let depths = parse(input)?;
+    Ok((
+        analyse(depths.iter().copied(), 1).increases,
+        analyse(depths, 3).increases,
+    ))
 }
 
 pub fn day_01() -> (usize, usize) {
-    let inputs = parse_ints("day_01".to_string());
-    (part_a(&inputs, 1), part_a(&inputs, 3))
+    solve(&read_file("day_01".to_string())).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day_01::{analyse, solve, validate, Sweep};
+    use aoc::fuzz::check_solver;
+
+    const EXAMPLE: [isize; 10] = [199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+
+    #[test]
+    fn example() {
+        let input = EXAMPLE.map(|d| d.to_string()).join("\n");
+        assert_eq!(solve(&input), Ok((7, 5)));
+        assert_eq!(
+            analyse(EXAMPLE, 1),
+            Sweep {
+                increases: 7,
+                decreases: 2,
+                plateaus: 0,
+                longest_increase: 3,
+            }
+        );
+        assert_eq!(
+            analyse(EXAMPLE, 3),
+            Sweep {
+                increases: 5,
+                decreases: 1,
+                plateaus: 1,
+                longest_increase: 4,
+            }
+        );
+    }
+
+    #[test]
+    fn streams_large_sweeps() {
+        // A sawtooth of period 10 increases 9 times in a row, then drops.
+        let sawtooth = (0..5_000_000).map(|i| i % 10);
+        assert_eq!(analyse(sawtooth.clone(), 10).plateaus, 5_000_000 - 10);
+        let sweep = analyse(sawtooth, 1);
+        assert_eq!(sweep.longest_increase, 9);
+        assert_eq!(sweep.decreases, 500_000 - 1);
+        assert_eq!(analyse(Vec::new(), 3), Sweep::default());
+    }
+
+    #[test]
+    fn rejects_depths_beyond_an_isize() {
+        let errors = validate("1\n9223372036854775808").unwrap_err();
+        assert_eq!(errors[0].line, Some(2));
+        assert!(solve("1\n9223372036854775807").is_ok());
+        assert!(validate("1\n-1").is_err());
+    }
+
+    // Inputs that once crashed the solution.
+    const REGRESSIONS: &[&str] = &["1\n9223372036854775808"];
+
+    #[test]
+    fn fuzz_solver() {
+        let example = EXAMPLE.map(|d| d.to_string()).join("\n");
+        check_solver(1, 2000, &[&example], REGRESSIONS, solve);
+    }
 }
@@ -1,8 +1,14 @@
-use aoc::parse_lines;
+use aoc::input::Input;
+use aoc::random::Rng;
+use aoc::read_file;
+use aoc::validate::check_each_line;
+use aoc::validate::Diagnostic;
+use std::convert::TryFrom;
 use std::str::FromStr;
 
+/// A single command of the planned course.
 #[derive(Debug, PartialEq)]
-enum Operation {
+pub enum Operation {
     Up(usize),
     Down(usize),
     Forward(usize),
@@ -12,9 +18,12 @@ impl FromStr for Operation {
     type Err = ();
 
     fn from_str(input: &str) -> Result<Operation, Self::Err> {
-        let mut split = input.split(" ");
-        let op = split.next().unwrap();
-        let val: usize = split.next().unwrap().parse().unwrap();
+        let mut split = input.split(' ');
+        let op = split.next().ok_or(())?;
+        let val: usize = split.next().ok_or(())?.parse().map_err(|_| ())?;
+        if split.next().is_some() {
+            return Err(());
+        }
         match op {
             "up" => Ok(Operation::Up(val)),
             "down" => Ok(Operation::Down(val)),
@@ -24,43 +33,227 @@ impl FromStr for Operation {
     }
 }
 
-fn part_a(operations: &Vec<Operation>) -> usize {
-    let mut x: usize = 0;
-    let mut y: usize = 0;
+/// Where the submarine is, a negative depth is above the surface.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Position {
+    pub horizontal: isize,
+    pub depth: isize,
+    pub aim: isize,
+}
 
-    for operation in operations {
-        match operation {
-            Operation::Forward(i) => x += i,
-            Operation::Down(i) => y += i,
-            Operation::Up(i) => y -= i,
+impl Position {
+    /// The product of the horizontal position and the depth, or `None` if it overflows.
+    pub fn product(&self) -> Option<isize> {
+        self.horizontal.checked_mul(self.depth)
+    }
+}
+
+/// The distance of a command as a signed step, or `None` if it does not fit.
+fn step(distance: usize) -> Option<isize> {
+    isize::try_from(distance).ok()
+}
+
+/// How a command moves the submarine, or `None` if the new position does not fit in an isize.
+pub trait Movement {
+    fn apply(&self, position: Position, operation: &Operation) -> Option<Position>;
+}
+
+/// Up and down change the depth directly.
+pub struct Plain;
+
+impl Movement for Plain {
+    fn apply(&self, position: Position, operation: &Operation) -> Option<Position> {
+        let mut next = position;
+        match *operation {
+            Operation::Forward(i) => next.horizontal = next.horizontal.checked_add(step(i)?)?,
+            Operation::Down(i) => next.depth = next.depth.checked_add(step(i)?)?,
+            Operation::Up(i) => next.depth = next.depth.checked_sub(step(i)?)?,
         }
+        Some(next)
     }
-    x * y
 }
 
-fn part_b(operations: &Vec<Operation>) -> usize {
-    let mut x: usize = 0;
-    let mut y: usize = 0;
-    let mut aim: usize = 0;
+/// Up and down change the aim, and moving forward also dives along the aim.
+pub struct Aimed;
 
-    for operation in operations {
-        match operation {
+impl Movement for Aimed {
+    fn apply(&self, position: Position, operation: &Operation) -> Option<Position> {
+        let mut next = position;
+        match *operation {
             Operation::Forward(i) => {
-                x += i;
-                y += aim * i;
+                next.horizontal = next.horizontal.checked_add(step(i)?)?;
+                next.depth = next.depth.checked_add(next.aim.checked_mul(step(i)?)?)?;
             }
-            Operation::Down(i) => aim += i,
-            Operation::Up(i) => aim -= i,
+            Operation::Down(i) => next.aim = next.aim.checked_add(step(i)?)?,
+            Operation::Up(i) => next.aim = next.aim.checked_sub(step(i)?)?,
         }
+        Some(next)
     }
-    x * y
 }
 
-pub fn day_02()  -> (usize, usize) {
-    let instructions = parse_lines("day_02".to_string());
-    let operations = instructions
+/// Run the commands from the origin, returns the position before the first and after every
+/// command, or `None` if the submarine goes further than an isize can tell.
+pub fn interpret(operations: &[Operation], movement: &dyn Movement) -> Option<Vec<Position>> {
+    let mut position = Position::default();
+    let mut trace = vec![position];
+    for operation in operations {
+        position = movement.apply(position, operation)?;
+        trace.push(position);
+    }
+    Some(trace)
+}
+
+/// Check that every line is a known command followed by a distance.
+pub fn validate(input: &str) -> Result<(), Vec<Diagnostic>> {
+    check_each_line(input, |validator, i, line| {
+        match line.split(' ').collect::<Vec<&str>>()[..] {
+            [command, distance] => {
+                validator.check(
+                    ["forward", "down", "up"].contains(&command),
+                    Some(i),
+                    &format!("unknown command '{}'", command),
+                );
+                validator.parse::<usize>(i, distance, "distance");
+            }
+            _ => validator.error(Some(i), "expected a command and a distance"),
+        }
+    })
+}
+
+/// Generate `size` commands that never take the submarine above the surface.
+pub fn generate(size: usize, seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+    let mut depth = 0;
+    let mut output = String::new();
+    for _ in 0..size {
+        let distance = rng.range(1..=9);
+        let command = match rng.below(3) {
+            0 => "forward",
+            1 if depth >= distance => "up",
+            _ => "down",
+        };
+        match command {
+            "up" => depth -= distance,
+            "down" => depth += distance,
+            _ => {}
+        }
+        output.push_str(&format!("{} {}\n", command, distance));
+    }
+    output
+}
+
+/// Parse the planned course.
+pub fn parse(input: &str) -> Result<Vec<Operation>, Vec<Diagnostic>> {
+    validate(input)?;
+    let input = Input::new(input);
+    Ok(input
+        .lines()
         .iter()
-        .map(|i| Operation::from_str(i).unwrap())
-        .collect();
-    (part_a(&operations), part_b(&operations))
+        .map(|l| Operation::from_str(l).unwrap())
+        .collect())
+}
+
+/// The product of the final horizontal position and depth, without and with aim.
+pub fn solve(input: &str) -> Result<(isize, isize), Vec<Diagnostic>> {
+    let operations = parse(input)?;
+    let product = |movement: &dyn Movement| {
+        interpret(&operations, movement)
+            .and_then(|trace| trace.last().unwrap().product())
+            .ok_or_else(|| {
+                vec![Diagnostic {
+                    line: None,
+                    message: "the course takes the submarine too far".to_string(),
+                }]
+            })
+    };
+    Ok((product(&Plain)?, product(&Aimed)?))
+}
+
+pub fn day_02() -> (usize, usize) {
+    let (a, b) = solve(&read_file("day_02".to_string())).unwrap();
+    // The runner shows unsigned answers, a course that ends above the surface has a negative one.
+    let unsigned = |p: isize| usize::try_from(p).expect("the course ends above the surface");
+    (unsigned(a), unsigned(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day_02::{interpret, parse, solve, validate, Aimed, Operation, Plain, Position};
+    use aoc::fuzz::check_parser;
+    use aoc::fuzz::check_solver;
+    use aoc::input::Input;
+    use aoc::validate::Diagnostic;
+    use std::str::FromStr;
+
+    const EXAMPLE: &str = "forward 5\ndown 5\nforward 8\nup 3\ndown 8\nforward 2";
+
+    #[test]
+    fn example() {
+        assert_eq!(solve(EXAMPLE), Ok((150, 900)));
+        let operations = parse(EXAMPLE).unwrap();
+        let trace = interpret(&operations, &Aimed).unwrap();
+        assert_eq!(trace.len(), 7);
+        assert_eq!(
+            trace[3],
+            Position {
+                horizontal: 13,
+                depth: 40,
+                aim: 5
+            }
+        );
+    }
+
+    #[test]
+    fn above_the_surface() {
+        let operations = parse("forward 2\nup 3\nforward 1").unwrap();
+        let plain = interpret(&operations, &Plain).unwrap();
+        assert_eq!(plain.last().unwrap().product(), Some(-9));
+        let aimed = interpret(&operations, &Aimed).unwrap();
+        assert_eq!(aimed.last().unwrap().depth, -3);
+    }
+
+    #[test]
+    fn unknown_command() {
+        assert_eq!(
+            parse("forward 5\nsideways 3"),
+            Err(vec![Diagnostic {
+                line: Some(2),
+                message: "unknown command 'sideways'".to_string()
+            }])
+        );
+    }
+
+    #[test]
+    fn too_far() {
+        let far = solve("down 4294967296\nforward 4294967296");
+        assert_eq!(
+            far.unwrap_err()[0].message,
+            "the course takes the submarine too far"
+        );
+        assert!(solve("forward 9223372036854775808").is_err());
+        assert!(solve("up 9223372036854775807\nup 1\nforward 1").is_ok());
+    }
+
+    // Inputs that once crashed the parser or the solution.
+    const REGRESSIONS: &[&str] = &[
+        "2",
+        "down 4294967296\nforward 4294967296",
+        "forward 9223372036854775808",
+    ];
+
+    #[test]
+    fn fuzz_parser() {
+        let parses = |input: &str| {
+            Input::new(input)
+                .lines()
+                .iter()
+                .all(|l| Operation::from_str(l).is_ok())
+        };
+        check_parser(2, 2000, &[EXAMPLE], REGRESSIONS, validate, parses);
+    }
+
+    #[test]
+    fn fuzz_solver() {
+        check_solver(2, 2000, &[EXAMPLE], REGRESSIONS, solve);
+    }
 }
@@ -1,99 +1,246 @@
-use aoc::from_bin;
-use aoc::parse_lines;
-use std::collections::HashSet;
-
-fn tally_to_binary_char(number: &isize, invert: bool) -> char {
-    if (number == &0) && invert {
-        '1'
-    } else if number == &0 {
-        '0'
-    } else if (number > &0) ^ !invert {
-        '1'
-    } else if (number < &0) ^ !invert {
-        '0'
-    } else {
-        panic!("unknown case")
-    }
+use aoc::input::Input;
+use aoc::random::Rng;
+use aoc::read_file;
+use aoc::validate::check_lines;
+use aoc::validate::Diagnostic;
+use std::convert::TryFrom;
+
+/// A diagnostic report of binary numbers that are all `width` bits wide, sorted.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Report {
+    pub width: usize,
+    pub values: Vec<usize>,
 }
 
-fn part_a(instructions: &Vec<String>) -> usize {
-    let mut array: [isize; 12] = [0; 12];
-
-    for instruction in instructions {
-        for (i, c) in instruction.chars().enumerate() {
-            match c {
-                '1' => array[i] += 1,
-                '0' => array[i] -= 1,
-                _ => {
-                    panic!("unknown case")
-                }
-            }
-        }
+/// The rates and ratings read from a report.
+///
+/// Every bit of gamma is the most common bit in that position, and epsilon is its complement, so a
+/// tie sets the bit in gamma. The oxygen rating keeps the numbers with the most common bit and
+/// the CO2 rating those with the least common bit. A tie keeps the ones for oxygen and the zeros
+/// for CO2, and when every number left shares a bit both ratings keep them all.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Rates {
+    pub gamma: usize,
+    pub epsilon: usize,
+    pub oxygen: usize,
+    pub co2: usize,
+}
+
+impl Rates {
+    pub fn power_consumption(&self) -> usize {
+        self.gamma * self.epsilon
     }
 
-    let gamma = from_bin(
-        &array
-            .iter()
-            .map(|s| tally_to_binary_char(s, false))
-            .collect::<Vec<char>>(),
-    );
-    let epsilon = from_bin(
-        &array
-            .iter()
-            .map(|s| tally_to_binary_char(s, true))
-            .collect::<Vec<char>>(),
-    );
-    gamma * epsilon
+    pub fn life_support(&self) -> usize {
+        self.oxygen * self.co2
+    }
 }
 
-fn recursive_filter(instruction_set: &HashSet<String>, index: usize, invert: bool) -> String {
-    let mut tally: isize = 0;
+/// The bit of `value` at position `bit`, bits beyond the width of a `usize` are zero.
+fn bit_at(value: usize, bit: usize) -> usize {
+    u32::try_from(bit)
+        .ok()
+        .and_then(|bit| value.checked_shr(bit))
+        .map_or(0, |v| v & 1)
+}
 
-    for instruction in instruction_set {
-        match instruction.chars().nth(index).unwrap() {
-            '1' => tally += 1,
-            '0' => tally -= 1,
-            _ => {
-                panic!("unknown case")
-            }
+/// Narrow the sorted values down bit by bit from the most significant one. The values left share
+/// every bit above the current one, so the values with the current bit set come last. An empty
+/// report rates 0.
+fn rating(values: &[usize], width: usize, most_common: bool) -> usize {
+    let mut left = values;
+    for bit in (0..width).rev() {
+        if left.len() == 1 {
+            break;
+        }
+        let (zeros, ones) = left.split_at(left.partition_point(|v| bit_at(*v, bit) == 0));
+        if zeros.is_empty() || ones.is_empty() {
+            continue;
         }
+        left = if most_common == (ones.len() >= zeros.len()) {
+            ones
+        } else {
+            zeros
+        };
     }
+    left.first().copied().unwrap_or(0)
+}
 
-    let target_char = tally_to_binary_char(&tally, invert);
-    let mut new_set: HashSet<String> = HashSet::new();
-
-    for instruction in instruction_set {
-        if instruction.chars().nth(index).unwrap() == target_char {
-            new_set.insert(instruction.to_string());
+/// Count the ones in every position in a single pass, the ratings only search the sorted values.
+pub fn rates(report: &Report) -> Rates {
+    let mut ones = vec![0; report.width];
+    for value in &report.values {
+        for (bit, count) in ones.iter_mut().enumerate() {
+            *count += bit_at(*value, bit);
         }
     }
-
-    if new_set.len() == 1 {
-        new_set.into_iter().next().unwrap()
+    let gamma = ones
+        .iter()
+        .enumerate()
+        .filter(|(_, count)| 2 * *count >= report.values.len())
+        .fold(0, |gamma, (bit, _)| gamma | 1 << bit);
+    let mask = if report.width >= usize::BITS as usize {
+        usize::MAX
     } else {
-        recursive_filter(&new_set, index + 1, invert)
+        (1 << report.width) - 1
+    };
+    Rates {
+        gamma,
+        epsilon: !gamma & mask,
+        oxygen: rating(&report.values, report.width, true),
+        co2: rating(&report.values, report.width, false),
     }
 }
 
-fn part_b(instructions: &Vec<String>) -> usize {
-    let instruction_set: HashSet<String> = instructions
+/// The widest report, which keeps the product of two of its numbers within a `usize`.
+const MAX_WIDTH: usize = usize::BITS as usize / 2;
+
+/// Check that the report consists of binary numbers of the same width, at most `MAX_WIDTH` bits.
+pub fn validate(input: &str) -> Result<(), Vec<Diagnostic>> {
+    check_lines(input, |validator, lines| {
+        if let Some(width) = validator.grid(lines, 0) {
+            validator.in_range(0, width, 1..=MAX_WIDTH, "report width");
+        }
+        for (i, line) in lines.iter().enumerate() {
+            validator.charset(i, line, "01");
+        }
+    })
+}
+
+/// Generate `size` distinct report lines of at least 12 bits.
+pub fn generate(size: usize, seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+    let width = 12.max(usize::BITS - size.leading_zeros() + 1) as usize;
+    let mut values = (0..1usize << width).collect::<Vec<usize>>();
+    rng.shuffle(&mut values);
+    values[..size]
+        .iter()
+        .map(|v| format!("{:0width$b}\n", v, width = width))
+        .collect()
+}
+
+/// Parse the diagnostic report.
+pub fn parse(input: &str) -> Result<Report, Vec<Diagnostic>> {
+    validate(input)?;
+    let input = Input::new(input);
+    let lines = input.lines();
+    let mut values = lines
         .iter()
-        .map(|s| s.clone())
-        .collect::<HashSet<String>>();
-    let oxygen = from_bin(
-        &recursive_filter(&instruction_set, 0, true)
-            .chars()
-            .collect::<Vec<char>>(),
-    );
-    let co2 = from_bin(
-        &recursive_filter(&instruction_set, 0, false)
-            .chars()
-            .collect::<Vec<char>>(),
-    );
-    oxygen * co2
+        .map(|l| usize::from_str_radix(l, 2).unwrap())
+        .collect::<Vec<usize>>();
+    values.sort_unstable();
+    Ok(Report {
+        width: lines[0].len(),
+        values,
+    })
+}
+
+/// The power consumption and the life support rating.
+pub fn solve(input: &str) -> Result<(usize, usize), Vec<Diagnostic>> {
+    let rates = rates(&parse(input)?);
+    Ok((rates.power_consumption(), rates.life_support()))
 }
 
 pub fn day_03() -> (usize, usize) {
-    let instructions = parse_lines("day_03".to_string());
-    (part_a(&instructions), part_b(&instructions))
+    solve(&read_file("day_03".to_string())).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day_03::{parse, rates, solve, validate, Rates, Report};
+    use aoc::fuzz::check_solver;
+
+    const EXAMPLE: &str =
+        "00100\n11110\n10110\n10111\n10101\n01111\n00111\n11100\n10000\n11001\n00010\n01010";
+
+    #[test]
+    fn example() {
+        assert_eq!(solve(EXAMPLE), Ok((198, 230)));
+        assert_eq!(
+            rates(&parse(EXAMPLE).unwrap()),
+            Rates {
+                gamma: 22,
+                epsilon: 9,
+                oxygen: 23,
+                co2: 10,
+            }
+        );
+    }
+
+    #[test]
+    fn ties_and_wide_reports() {
+        // Both bits are tied, which sets them in gamma. The oxygen rating keeps the ones and the
+        // CO2 rating the zeros.
+        let report = Report {
+            width: 2,
+            values: vec![0b00, 0b01, 0b10, 0b11],
+        };
+        assert_eq!(
+            rates(&report),
+            Rates {
+                gamma: 0b11,
+                epsilon: 0b00,
+                oxygen: 0b11,
+                co2: 0b00,
+            }
+        );
+
+        let report = Report {
+            width: 64,
+            values: vec![1, 1 << 63, usize::MAX],
+        };
+        let rates = rates(&report);
+        assert_eq!(rates.gamma, 1 << 63 | 1);
+        assert_eq!(rates.epsilon, !(1 << 63 | 1));
+        assert_eq!((rates.oxygen, rates.co2), (usize::MAX, 1));
+    }
+
+    #[test]
+    fn degenerate_widths() {
+        let report = Report {
+            width: 0,
+            values: vec![0, 0],
+        };
+        assert_eq!(rates(&report).power_consumption(), 0);
+
+        let report = Report {
+            width: 70,
+            values: vec![1, 2],
+        };
+        let rates = rates(&report);
+        assert_eq!((rates.gamma, rates.epsilon), (0b11, !0b11));
+        assert_eq!((rates.oxygen, rates.co2), (2, 1));
+    }
+
+    #[test]
+    fn ties_set_gamma() {
+        // The middle bit is tied, so it is set in gamma rather than in epsilon.
+        let rates = rates(&parse("100\n110\n111\n000").unwrap());
+        assert_eq!((rates.gamma, rates.epsilon), (0b110, 0b001));
+        assert_eq!(rates.power_consumption(), 6);
+    }
+
+    #[test]
+    fn rejects_wide_and_empty_reports() {
+        let wide = "1".repeat(40);
+        assert!(validate(&format!("{}\n{}", wide, wide)).is_err());
+        assert!(validate(&"1".repeat(32)).is_ok());
+        assert!(validate("").is_err());
+        let empty = Report {
+            width: 5,
+            values: Vec::new(),
+        };
+        assert_eq!(rates(&empty).life_support(), 0);
+    }
+
+    // Inputs that once crashed the solution.
+    const REGRESSIONS: &[&str] = &[
+        "1000000000000000000000000000000000000000\n0000000000000000000000000000000000000001",
+        "",
+    ];
+
+    #[test]
+    fn fuzz_solver() {
+        check_solver(3, 2000, &[EXAMPLE], REGRESSIONS, solve);
+    }
 }
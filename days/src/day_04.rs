@@ -1,115 +1,371 @@
-use aoc::ints_from_str;
-use aoc::parse_items;
+use aoc::input::Input;
+use aoc::random::Rng;
+use aoc::read_file;
+use aoc::validate::check_lines;
+use aoc::validate::sections;
+use aoc::validate::Diagnostic;
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::str::FromStr;
 
+/// A square board of numbers, stored row by row.
 #[derive(Clone, Debug, Eq, PartialEq)]
-struct BingoBoard {
-    rows: Vec<HashSet<isize>>,
+pub struct BingoBoard {
+    size: usize,
+    numbers: Vec<isize>,
 }
 
 impl FromStr for BingoBoard {
     type Err = ();
 
     fn from_str(input: &str) -> Result<BingoBoard, Self::Err> {
-        let dimension = 5;
-        let numbers = ints_from_str(&input.to_string());
-        let mut rows = Vec::new();
-
-        // Get the horizonal rows.
-        for i in 0..dimension {
-            let mut set = HashSet::new();
-            for j in 0..dimension {
-                set.insert(*numbers.get(i * dimension + j).unwrap());
-            }
-            rows.push(set);
+        let rows = input
+            .lines()
+            .map(|l| {
+                l.split_whitespace()
+                    .map(|n| n.parse().map_err(|_| ()))
+                    .collect::<Result<Vec<isize>, ()>>()
+            })
+            .collect::<Result<Vec<Vec<isize>>, ()>>()?;
+        let size = rows.len();
+        if size == 0 || rows.iter().any(|r| r.len() != size) {
+            return Err(());
         }
+        Ok(BingoBoard {
+            size,
+            numbers: rows.concat(),
+        })
+    }
+}
 
-        // Get the vertical rows.
-        for i in 0..dimension {
-            let mut set = HashSet::new();
-            for j in 0..dimension {
-                set.insert(*numbers.get(i + j * dimension).unwrap());
-            }
-            rows.push(set);
-        }
+impl BingoBoard {
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Whether every number of a row or a column has been drawn.
+    pub fn check(&self, drawn: &HashSet<isize>) -> bool {
+        let marked = |i: usize| drawn.contains(&self.numbers[i]);
+        (0..self.size).any(|r| (0..self.size).all(|c| marked(r * self.size + c)))
+            || (0..self.size).any(|c| (0..self.size).all(|r| marked(r * self.size + c)))
+    }
 
-        return Ok(BingoBoard { rows });
+    /// The sum of the numbers on the board that have not been drawn.
+    pub fn score(&self, drawn: &HashSet<isize>) -> isize {
+        self.numbers.iter().filter(|n| !drawn.contains(n)).sum()
     }
 }
 
-impl BingoBoard {
-    fn check(&self, drawn: &HashSet<isize>) -> bool {
-        for row in &self.rows {
-            if row.is_subset(drawn) {
-                return true;
-            }
+/// A board that got a bingo, and its final score: the number that completed the line times the sum
+/// of the numbers that were not drawn.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Win {
+    pub board: usize,
+    pub number: isize,
+    pub score: isize,
+}
+
+/// How many numbers have been marked in every line of a board.
+struct Marks {
+    marked: Vec<bool>,
+    rows: Vec<usize>,
+    columns: Vec<usize>,
+    diagonals: [usize; 2],
+    unmarked: isize,
+    won: bool,
+}
+
+impl Marks {
+    fn new(board: &BingoBoard) -> Marks {
+        Marks {
+            marked: vec![false; board.numbers.len()],
+            rows: vec![0; board.size],
+            columns: vec![0; board.size],
+            diagonals: [0, 0],
+            unmarked: board.numbers.iter().sum(),
+            won: false,
         }
-        return false;
     }
 
-    fn score(&self, drawn: &HashSet<isize>) -> isize {
-        let all = self
-            .rows
-            .iter()
-            .fold(HashSet::new(), |i, j| i.union(&j).map(|i| *i).collect());
-        let some = all.difference(drawn);
-        some.sum()
+    /// Mark a cell, returns whether it completes a line.
+    fn mark(&mut self, board: &BingoBoard, cell: usize, diagonals: bool) -> bool {
+        if self.marked[cell] {
+            return false;
+        }
+        self.marked[cell] = true;
+        self.unmarked -= board.numbers[cell];
+        let (row, column) = (cell / board.size, cell % board.size);
+        self.rows[row] += 1;
+        self.columns[column] += 1;
+        let mut complete = self.rows[row] == board.size || self.columns[column] == board.size;
+        if diagonals {
+            if row == column {
+                self.diagonals[0] += 1;
+                complete |= self.diagonals[0] == board.size;
+            }
+            if row + column + 1 == board.size {
+                self.diagonals[1] += 1;
+                complete |= self.diagonals[1] == board.size;
+            }
+        }
+        complete
     }
 }
 
-fn part_a(raw_inputs: &Vec<String>) -> isize {
-    let numbers_to_draw: Vec<isize> = ints_from_str(raw_inputs.get(0).unwrap());
-    let slice = &raw_inputs[1..raw_inputs.len()];
-    let boards: Vec<BingoBoard> = slice
-        .iter()
-        .map(|s| BingoBoard::from_str(s).unwrap())
-        .collect();
-
-    let mut drawn_numbers = HashSet::new();
-    for drawn_number in numbers_to_draw {
-        drawn_numbers.insert(drawn_number);
-        for board in &boards {
-            if board.check(&drawn_numbers) {
-                return drawn_number * board.score(&drawn_numbers);
+/// Draw the numbers and return every board that wins in the order in which they win, boards that
+/// win on the same number are ordered as given. With `diagonals` a full diagonal also counts as a
+/// bingo. Every draw only touches the cells holding that number.
+pub fn play(numbers_to_draw: &[isize], boards: &[BingoBoard], diagonals: bool) -> Vec<Win> {
+    let mut cells: HashMap<isize, Vec<(usize, usize)>> = HashMap::new();
+    for (b, board) in boards.iter().enumerate() {
+        for (cell, number) in board.numbers.iter().enumerate() {
+            cells.entry(*number).or_default().push((b, cell));
+        }
+    }
+    let mut marks = boards.iter().map(Marks::new).collect::<Vec<Marks>>();
+    let mut wins = Vec::new();
+    for &number in numbers_to_draw {
+        for &(b, cell) in cells.get(&number).into_iter().flatten() {
+            let marks = &mut marks[b];
+            if !marks.won && marks.mark(&boards[b], cell, diagonals) {
+                marks.won = true;
+                wins.push(Win {
+                    board: b,
+                    number,
+                    score: number * marks.unmarked,
+                });
             }
         }
+        if wins.len() == boards.len() {
+            break;
+        }
     }
-    panic!("no solution found")
+    wins
 }
 
-fn part_b(raw_inputs: &Vec<String>) -> isize {
-    let numbers_to_draw: Vec<isize> = ints_from_str(raw_inputs.get(0).unwrap());
-    let slice = &raw_inputs[1..raw_inputs.len()];
-    let boards: Vec<BingoBoard> = slice
+fn parse_sections(sections: &[Input]) -> Result<(Vec<isize>, Vec<BingoBoard>), ()> {
+    let numbers_to_draw = sections
+        .first()
+        .ok_or(())?
+        .as_str()
+        .split(',')
+        .map(|n| n.parse().map_err(|_| ()))
+        .collect::<Result<Vec<isize>, ()>>()?;
+    let boards = sections[1..]
         .iter()
-        .map(|s| BingoBoard::from_str(s).unwrap())
-        .collect();
-
-    let mut drawn_numbers = HashSet::new();
-    let mut finished_boards = Vec::new();
-    for drawn_number in numbers_to_draw {
-        drawn_numbers.insert(drawn_number);
-        for board in &boards {
-            // Only consider boards that are not finished up on this point.
-            if !finished_boards.contains(&board) {
-                // Only consider boards that have a bingo with the new number.
-                if board.check(&drawn_numbers) {
-                    // Remember that this board was completed already.
-                    finished_boards.push(board);
-
-                    // If this is the last board, return a result.
-                    if finished_boards.len() == boards.len() {
-                        return drawn_number * board.score(&drawn_numbers);
+        .map(|s| BingoBoard::from_str(s.as_str()))
+        .collect::<Result<Vec<BingoBoard>, ()>>()?;
+    Ok((numbers_to_draw, boards))
+}
+
+/// The largest number, which keeps the scores of boards of fewer than 2^30 numbers within an isize.
+const LIMIT: isize = 1 << 16;
+
+/// Check that the numbers to draw are followed by square boards.
+pub fn validate(input: &str) -> Result<(), Vec<Diagnostic>> {
+    check_lines(input, |validator, lines| {
+        let sections = sections(lines);
+        if !validator.check(
+            sections.len() >= 2,
+            None,
+            "expected the numbers to draw followed by at least one board",
+        ) {
+            return;
+        }
+
+        let (start, draws) = &sections[0];
+        validator.check(
+            draws.len() == 1,
+            Some(*start),
+            "the numbers to draw should be on a single line",
+        );
+        for number in draws[0].split(',') {
+            if let Some(n) = validator.parse::<isize>(*start, number, "number to draw") {
+                validator.in_range(*start, n, 0..=LIMIT, "number to draw");
+            }
+        }
+        for (start, board) in &sections[1..] {
+            for (i, row) in board.iter().enumerate() {
+                let numbers = row.split_whitespace().collect::<Vec<&str>>();
+                validator.check(
+                    numbers.len() == board.len(),
+                    Some(start + i),
+                    &format!(
+                        "expected {} numbers for a board of {} rows, found {}",
+                        board.len(),
+                        board.len(),
+                        numbers.len()
+                    ),
+                );
+                for number in numbers {
+                    if let Some(n) = validator.parse::<isize>(start + i, number, "board number") {
+                        validator.in_range(start + i, n, 0..=LIMIT, "board number");
                     }
                 }
             }
         }
+    })
+}
+
+/// Generate the numbers 0 to 99 in a random order followed by `size` boards, so that every board
+/// wins eventually.
+pub fn generate(size: usize, seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+    let mut numbers = (0..100).collect::<Vec<usize>>();
+    rng.shuffle(&mut numbers);
+    let draws = numbers
+        .iter()
+        .map(|n| n.to_string())
+        .collect::<Vec<String>>();
+    let mut output = format!("{}\n", draws.join(","));
+    for _ in 0..size {
+        rng.shuffle(&mut numbers);
+        output.push('\n');
+        for row in numbers[..25].chunks(5) {
+            let row = row
+                .iter()
+                .map(|n| format!("{:>2}", n))
+                .collect::<Vec<String>>();
+            output.push_str(&format!("{}\n", row.join(" ")));
+        }
+    }
+    output
+}
+
+/// Parse the numbers to draw and the boards.
+pub fn parse(input: &str) -> Result<(Vec<isize>, Vec<BingoBoard>), Vec<Diagnostic>> {
+    validate(input)?;
+    parse_sections(&Input::new(input).sections()).map_err(|_| {
+        vec![Diagnostic {
+            line: None,
+            message: "the numbers to draw or the boards are malformed".to_string(),
+        }]
+    })
+}
+
+/// The final score of the first board to win, and of the last board to win.
+pub fn solve(input: &str) -> Result<(isize, isize), Vec<Diagnostic>> {
+    let (numbers_to_draw, boards) = parse(input)?;
+    let wins = play(&numbers_to_draw, &boards, false);
+    match (wins.first(), wins.last()) {
+        (Some(first), Some(last)) => Ok((first.score, last.score)),
+        _ => Err(vec![Diagnostic {
+            line: None,
+            message: "no board wins with the numbers drawn".to_string(),
+        }]),
     }
-    panic!("no solution found")
 }
 
 pub fn day_04() -> (usize, usize) {
-    let instructions = parse_items("day_04".to_string(), "\n\n".to_string());
-    (part_a(&instructions) as usize, part_b(&instructions) as usize)
+    let (a, b) = solve(&read_file("day_04".to_string())).unwrap();
+    (a as usize, b as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day_04::{parse, parse_sections, play, solve, validate, Win};
+    use aoc::fuzz::check_parser;
+    use aoc::fuzz::check_solver;
+    use aoc::input::Input;
+    use aoc::validate::Diagnostic;
+    use std::collections::HashSet;
+
+    const EXAMPLE: &str = "7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
+
+22 13 17 11  0
+ 8  2 23  4 24
+21  9 14 16  7
+ 6 10  3 18  5
+ 1 12 20 15 19
+
+ 3 15  0  2 22
+ 9 18 13 17  5
+19  8  7 25 23
+20 11 10 24  4
+14 21 16 12  6
+
+14 21 17 24  4
+10 16 15  9 19
+18  8 23 26 20
+22 11 13  6  5
+ 2  0 12  3  7";
+
+    #[test]
+    fn example() {
+        assert_eq!(solve(EXAMPLE), Ok((4512, 1924)));
+        let (numbers, boards) = parse(EXAMPLE).unwrap();
+        let order = play(&numbers, &boards, false)
+            .iter()
+            .map(|w| (w.board, w.number))
+            .collect::<Vec<(usize, isize)>>();
+        assert_eq!(order, vec![(2, 24), (0, 16), (1, 13)]);
+
+        let drawn = numbers[..12].iter().copied().collect::<HashSet<isize>>();
+        assert!(boards[2].check(&drawn));
+        assert!(!boards[0].check(&drawn));
+        assert_eq!(boards[2].score(&drawn), 188);
+    }
+
+    #[test]
+    fn no_board_wins() {
+        let input = "1,5\n\n1 2\n3 4";
+        assert!(validate(input).is_ok());
+        assert_eq!(
+            solve(input),
+            Err(vec![Diagnostic {
+                line: None,
+                message: "no board wins with the numbers drawn".to_string()
+            }])
+        );
+    }
+
+    #[test]
+    fn diagonals_and_other_sizes() {
+        let (numbers, boards) = parse("1,5,9,2\n\n1 2 3\n4 5 6\n7 8 9\n\n9 1\n3 4").unwrap();
+        assert_eq!(boards[0].size(), 3);
+        assert_eq!(
+            play(&numbers, &boards, true),
+            vec![
+                Win {
+                    board: 0,
+                    number: 9,
+                    score: 270,
+                },
+                Win {
+                    board: 1,
+                    number: 9,
+                    score: 63,
+                },
+            ]
+        );
+        assert_eq!(play(&numbers, &boards, false)[0].board, 1);
+    }
+
+    #[test]
+    fn rejects_large_numbers() {
+        assert!(validate("65536\n\n65536").is_ok());
+        assert!(validate("65537\n\n1").is_err());
+        assert!(validate("1\n\n9223372036854775808").is_err());
+    }
+
+    // Inputs that once crashed the parser or the solution, or passed validation but failed to
+    // parse.
+    const REGRESSIONS: &[&str] = &[
+        "",
+        "9223372036854775807\n\n9223372036854775807",
+        "1\n\n1 2",
+        "99999999999999999999",
+        "4\n\n2 3 7 1 0\n8 2 3 4 4\n1,9 4 6 7\n6 0 3 8 5\n1 2 0 5 1",
+    ];
+
+    #[test]
+    fn fuzz_parser() {
+        let parses = |input: &str| parse_sections(&Input::new(input).sections()).is_ok();
+        check_parser(4, 2000, &[EXAMPLE], REGRESSIONS, validate, parses);
+    }
+
+    #[test]
+    fn fuzz_solver() {
+        check_solver(4, 2000, &[EXAMPLE], REGRESSIONS, solve);
+    }
 }
@@ -1,98 +1,611 @@
-use aoc::ints_from_str;
-use aoc::parse_lines;
-use std::cmp;
+use aoc::input::Input;
+use aoc::random::Rng;
+use aoc::read_file;
+use aoc::render::grid_to_text;
+use aoc::render::invalid_input;
+use aoc::render::FrameWriter;
+use aoc::validate::check_each_line;
+use aoc::validate::Diagnostic;
+use std::collections::HashMap;
 use std::collections::HashSet;
+use std::io;
+use std::path::Path;
 use std::str::FromStr;
 
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
-struct Vent {
-    x: isize,
-    y: isize,
+/// A single hydrothermal vent.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Vent {
+    pub x: isize,
+    pub y: isize,
 }
 
+/// The direction of a line of vents, a single vent counts as horizontal.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Orientation {
+    Horizontal,
+    Vertical,
+    /// Going down and to the right, along which `x - y` is constant.
+    Diagonal,
+    /// Going up and to the right, along which `x + y` is constant.
+    AntiDiagonal,
+}
+
+impl Orientation {
+    /// The line through `vent` as `a * x + b * y = c`.
+    fn line(&self, vent: Vent) -> (isize, isize, isize) {
+        let (a, b) = match self {
+            Orientation::Horizontal => (0, 1),
+            Orientation::Vertical => (1, 0),
+            Orientation::Diagonal => (1, -1),
+            Orientation::AntiDiagonal => (1, 1),
+        };
+        (a, b, a * vent.x + b * vent.y)
+    }
+
+    /// The position of `vent` along a line in this direction.
+    fn position(&self, vent: Vent) -> isize {
+        match self {
+            Orientation::Vertical => vent.y,
+            _ => vent.x,
+        }
+    }
+
+    /// The vent at `position` along the line `a * x + b * y = c` in this direction.
+    fn vent(&self, c: isize, position: isize) -> Vent {
+        match self {
+            Orientation::Horizontal => Vent { x: position, y: c },
+            Orientation::Vertical => Vent { x: c, y: position },
+            Orientation::Diagonal => Vent {
+                x: position,
+                y: position - c,
+            },
+            Orientation::AntiDiagonal => Vent {
+                x: position,
+                y: c - position,
+            },
+        }
+    }
+}
+
+const ORIENTATIONS: [Orientation; 4] = [
+    Orientation::Horizontal,
+    Orientation::Vertical,
+    Orientation::Diagonal,
+    Orientation::AntiDiagonal,
+];
+
+/// A horizontal, vertical or diagonal line of vents between two ends.
 #[derive(Clone, Debug, Eq, PartialEq)]
-struct VentRow {
-    diagonal: bool,
-    vents: HashSet<Vent>,
+pub struct VentRow {
+    pub start: Vent,
+    pub end: Vent,
+}
+
+/// The two ends of a segment such as `0,9 -> 5,9`, as written.
+fn ends(input: &str) -> Option<[(&str, &str); 2]> {
+    let (start, end) = input.split_once(" -> ")?;
+    Some([start.split_once(',')?, end.split_once(',')?])
+}
+
+/// Whether a segment is horizontal, vertical or diagonal.
+fn is_straight(start: Vent, end: Vent) -> bool {
+    let (dx, dy) = (start.x.abs_diff(end.x), start.y.abs_diff(end.y));
+    dx == 0 || dy == 0 || dx == dy
 }
 
 impl FromStr for VentRow {
     type Err = ();
 
     fn from_str(input: &str) -> Result<VentRow, Self::Err> {
-        let numbers = ints_from_str(&input.to_string());
-        let mut vents = HashSet::new();
-
-        let x0 = *numbers.get(0).unwrap();
-        let x1 = *numbers.get(2).unwrap();
-        let y0 = *numbers.get(1).unwrap();
-        let y1 = *numbers.get(3).unwrap();
-
-        // Horizontal and vertical.
-        for x in cmp::min(x0, x1)..=cmp::max(x0, x1) {
-            for y in cmp::min(y0, y1)..=cmp::max(y0, y1) {
-                if x0 == x1 || y0 == y1 {
-                    vents.insert(Vent { x, y });
-                }
+        let vent = |(x, y): (&str, &str)| -> Result<Vent, ()> {
+            Ok(Vent {
+                x: x.parse().map_err(|_| ())?,
+                y: y.parse().map_err(|_| ())?,
+            })
+        };
+        let [start, end] = ends(input).ok_or(())?;
+        let (start, end) = (vent(start)?, vent(end)?);
+        if is_straight(start, end) {
+            Ok(VentRow { start, end })
+        } else {
+            Err(())
+        }
+    }
+}
+
+impl VentRow {
+    pub fn orientation(&self) -> Orientation {
+        let (dx, dy) = (self.end.x - self.start.x, self.end.y - self.start.y);
+        if dy == 0 {
+            Orientation::Horizontal
+        } else if dx == 0 {
+            Orientation::Vertical
+        } else if (dx > 0) == (dy > 0) {
+            Orientation::Diagonal
+        } else {
+            Orientation::AntiDiagonal
+        }
+    }
+
+    /// The number of vents in the row.
+    pub fn length(&self) -> usize {
+        let (dx, dy) = (self.end.x - self.start.x, self.end.y - self.start.y);
+        dx.unsigned_abs().max(dy.unsigned_abs()) + 1
+    }
+
+    /// The vents in the row, from its start to its end.
+    pub fn vents(&self) -> impl Iterator<Item = Vent> {
+        let start = self.start;
+        let (dx, dy) = (
+            (self.end.x - start.x).signum(),
+            (self.end.y - start.y).signum(),
+        );
+        (0..self.length() as isize).map(move |i| Vent {
+            x: start.x + i * dx,
+            y: start.y + i * dy,
+        })
+    }
+
+    pub fn is_diagonal(&self) -> bool {
+        matches!(
+            self.orientation(),
+            Orientation::Diagonal | Orientation::AntiDiagonal
+        )
+    }
+
+    /// The line the row lies on, and the range of positions it covers along it.
+    fn span(&self) -> ((Orientation, isize), (isize, isize)) {
+        let orientation = self.orientation();
+        let (_, _, c) = orientation.line(self.start);
+        let (a, b) = (
+            orientation.position(self.start),
+            orientation.position(self.end),
+        );
+        ((orientation, c), (a.min(b), a.max(b)))
+    }
+}
+
+/// The vent where two lines in different directions cross, if they cross on a whole coordinate.
+fn crossing(first: (Orientation, isize), second: (Orientation, isize)) -> Option<Vent> {
+    // Both lines are given as `a * x + b * y = c`, solve for x and y with Cramer's rule.
+    let (a1, b1, _) = first.0.line(Vent { x: 0, y: 0 });
+    let (a2, b2, _) = second.0.line(Vent { x: 0, y: 0 });
+    let (c1, c2) = (first.1, second.1);
+    let determinant = a1 * b2 - a2 * b1;
+    let (x, y) = (c1 * b2 - c2 * b1, a1 * c2 - a2 * c1);
+    if determinant == 0 || x % determinant != 0 || y % determinant != 0 {
+        return None;
+    }
+    Some(Vent {
+        x: x / determinant,
+        y: y / determinant,
+    })
+}
+
+/// Where the vent rows overlap: for every line the ranges covered by at least two rows on it, and
+/// the vents where rows on different lines cross.
+struct Overlaps {
+    ranges: HashMap<(Orientation, isize), Vec<(isize, isize)>>,
+    crossings: HashSet<Vent>,
+}
+
+impl Overlaps {
+    fn new(vent_rows: &[VentRow], diagonals: bool) -> Overlaps {
+        let mut lines: HashMap<(Orientation, isize), Vec<(isize, isize)>> = HashMap::new();
+        for vent_row in vent_rows {
+            if diagonals || !vent_row.is_diagonal() {
+                let (line, span) = vent_row.span();
+                lines.entry(line).or_default().push(span);
             }
         }
 
-        // Diagonal, less elegant because Rust does not appear to like descending ranges,
-        let mut x = x0;
-        let mut y = y0;
-        while (x != x1) && (y != y1) {
-            vents.insert(Vent { x, y });
-            if x0 != x1 {
-                x += (x1 - x0) / (x1 - x0).abs();
+        // Sweep along every line, counting how many rows cover every position.
+        let mut ranges = HashMap::new();
+        for (line, spans) in &lines {
+            let mut events = spans
+                .iter()
+                .flat_map(|&(start, end)| [(start, 1), (end + 1, -1)])
+                .collect::<Vec<(isize, isize)>>();
+            events.sort_unstable();
+            let mut covering = 0;
+            let mut overlapping = Vec::new();
+            for (position, change) in events {
+                if covering >= 2 && covering + change < 2 {
+                    let start = overlapping.pop().unwrap();
+                    if position > start {
+                        overlapping.push(start);
+                        overlapping.push(position - 1);
+                    }
+                } else if covering < 2 && covering + change >= 2 {
+                    overlapping.push(position);
+                }
+                covering += change;
             }
-            if y0 != y1 {
-                y += (y1 - y0) / (y1 - y0).abs();
+            let overlapping = overlapping
+                .chunks(2)
+                .map(|r| (r[0], r[1]))
+                .collect::<Vec<(isize, isize)>>();
+            if !overlapping.is_empty() {
+                ranges.insert(*line, overlapping);
             }
+        }
 
-            if (x == x1) && (y == y1) {
-                vents.insert(Vent { x, y });
+        // Rows on different lines cross in at most one vent.
+        let lines = lines.into_iter().collect::<Vec<_>>();
+        let mut crossings = HashSet::new();
+        for (i, (first, first_spans)) in lines.iter().enumerate() {
+            for (second, second_spans) in &lines[i + 1..] {
+                let vent = match crossing(*first, *second) {
+                    Some(vent) => vent,
+                    None => continue,
+                };
+                let covers = |line: &(Orientation, isize), spans: &[(isize, isize)]| {
+                    let position = line.0.position(vent);
+                    spans.iter().any(|&(s, e)| (s..=e).contains(&position))
+                };
+                if covers(first, first_spans) && covers(second, second_spans) {
+                    crossings.insert(vent);
+                }
             }
         }
+        Overlaps { ranges, crossings }
+    }
 
-        let diagonal = !(x0 == x1 || y0 == y1);
+    /// Whether `vent` is covered by at least two rows on the line through it in `orientation`.
+    fn overlaps_on_line(&self, orientation: Orientation, vent: Vent) -> bool {
+        let (_, _, c) = orientation.line(vent);
+        let position = orientation.position(vent);
+        self.ranges.get(&(orientation, c)).is_some_and(|ranges| {
+            let i = ranges.partition_point(|&(_, end)| end < position);
+            i < ranges.len() && ranges[i].0 <= position
+        })
+    }
 
-        return Ok(VentRow { diagonal, vents });
+    fn count(&self) -> usize {
+        let on_lines = self
+            .ranges
+            .values()
+            .flatten()
+            .map(|(start, end)| (end - start + 1) as usize)
+            .sum::<usize>();
+        // A crossing is counted once, also when it overlaps on one or more of its lines.
+        let counted_on_lines = self
+            .crossings
+            .iter()
+            .map(|&vent| {
+                ORIENTATIONS
+                    .iter()
+                    .filter(|o| self.overlaps_on_line(**o, vent))
+                    .count()
+            })
+            .sum::<usize>();
+        on_lines + self.crossings.len() - counted_on_lines
+    }
+
+    fn vents(&self) -> Vec<Vent> {
+        let mut vents = self.crossings.clone();
+        for ((orientation, c), ranges) in &self.ranges {
+            for &(start, end) in ranges {
+                vents.extend((start..=end).map(|p| orientation.vent(*c, p)));
+            }
+        }
+        let mut vents = vents.into_iter().collect::<Vec<Vent>>();
+        vents.sort_unstable();
+        vents
     }
 }
 
-impl VentRow {
-    fn overlaps(&self, other: &HashSet<Vent>) -> HashSet<Vent> {
-        self.vents
-            .intersection(&other)
-            .map(|v| v.clone())
-            .collect::<HashSet<Vent>>()
+/// The number of vents covered by at least two rows. Only the rows are swept and crossed, so the
+/// work does not depend on how long they are.
+pub fn count_overlaps(vent_rows: &[VentRow], diagonals: bool) -> usize {
+    Overlaps::new(vent_rows, diagonals).count()
+}
+
+/// The vents covered by at least two rows, ordered by x and then y.
+pub fn overlapping_vents(vent_rows: &[VentRow], diagonals: bool) -> Vec<Vent> {
+    Overlaps::new(vent_rows, diagonals).vents()
+}
+
+/// How many rows cover every vent of a field.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Coverage {
+    /// The vent in the top left corner.
+    pub origin: Vent,
+    pub counts: Vec<Vec<usize>>,
+}
+
+impl Coverage {
+    /// A row of `.` and counts per line as in the puzzle, counts above 9 are drawn as `#`.
+    pub fn to_text(&self) -> String {
+        grid_to_text(&self.counts, |c| match c {
+            0 => '.',
+            1..=9 => char::from_digit(*c as u32, 10).unwrap(),
+            _ => '#',
+        })
+    }
+
+    /// Grey values scaled so that the most covered vents are white.
+    pub fn heatmap(&self) -> Vec<Vec<u8>> {
+        let most = *self.counts.iter().flatten().max().unwrap_or(&1).max(&1);
+        self.counts
+            .iter()
+            .map(|r| r.iter().map(|c| (c * 255 / most) as u8).collect())
+            .collect()
     }
 }
 
-fn solve(raw_inputs: &Vec<String>, diagonals: bool) -> usize {
-    let vent_rows: Vec<VentRow> = raw_inputs
+/// Count how many rows cover every vent within the bounding box of all rows, also those left out
+/// when `diagonals` is false, so that fields with and without diagonals line up. The grid is
+/// dense, so this is meant for looking at a field rather than for huge coordinates.
+pub fn coverage(vent_rows: &[VentRow], diagonals: bool) -> Coverage {
+    let ends = vent_rows
         .iter()
-        .map(|s| VentRow::from_str(s).unwrap())
-        .collect();
-
-    let mut taken: HashSet<Vent> = HashSet::new();
-    let mut overlaps: HashSet<Vent> = HashSet::new();
-
-    for vent_row in &vent_rows {
-        // Filter out diagonals if necessary.
-        if !vent_row.diagonal || diagonals {
-            overlaps = overlaps
-                .union(&vent_row.overlaps(&taken))
-                .map(|v| v.clone())
-                .collect();
-            taken = taken.union(&vent_row.vents).map(|v| v.clone()).collect();
+        .flat_map(|r| [r.start, r.end])
+        .collect::<Vec<Vent>>();
+    if ends.is_empty() {
+        return Coverage {
+            origin: Vent { x: 0, y: 0 },
+            counts: Vec::new(),
+        };
+    }
+    let origin = Vent {
+        x: ends.iter().map(|v| v.x).min().unwrap(),
+        y: ends.iter().map(|v| v.y).min().unwrap(),
+    };
+    let width = (ends.iter().map(|v| v.x).max().unwrap() - origin.x + 1) as usize;
+    let height = (ends.iter().map(|v| v.y).max().unwrap() - origin.y + 1) as usize;
+    let mut counts = vec![vec![0; width]; height];
+    for vent_row in vent_rows {
+        if diagonals || !vent_row.is_diagonal() {
+            for vent in vent_row.vents() {
+                counts[(vent.y - origin.y) as usize][(vent.x - origin.x) as usize] += 1;
+            }
         }
     }
+    Coverage { origin, counts }
+}
 
-    overlaps.len()
+/// One line per row with its ends, orientation and length, followed by the totals per
+/// orientation.
+pub fn statistics(vent_rows: &[VentRow]) -> String {
+    let mut output = String::new();
+    for vent_row in vent_rows {
+        output.push_str(&format!(
+            "{},{} -> {},{}: {:?}, {} vents\n",
+            vent_row.start.x,
+            vent_row.start.y,
+            vent_row.end.x,
+            vent_row.end.y,
+            vent_row.orientation(),
+            vent_row.length()
+        ));
+    }
+    for orientation in ORIENTATIONS {
+        let rows = vent_rows
+            .iter()
+            .filter(|r| r.orientation() == orientation)
+            .collect::<Vec<&VentRow>>();
+        output.push_str(&format!(
+            "{:?}: {} rows, {} vents\n",
+            orientation,
+            rows.len(),
+            rows.iter().map(|r| r.length()).sum::<usize>()
+        ));
+    }
+    output
+}
+
+/// Write the coverage of the field without and with diagonals, as text and as PGM heatmaps, and
+/// the statistics of every row.
+pub fn render(input: &str, directory: &Path) -> io::Result<()> {
+    let vent_rows = parse(input).map_err(invalid_input)?;
+    let mut frames = FrameWriter::new(directory, "day_05")?;
+    for diagonals in [false, true] {
+        let coverage = coverage(&vent_rows, diagonals);
+        frames.write_text(&coverage.to_text())?;
+        frames.write_pgm(&coverage.heatmap())?;
+    }
+    frames.write_text(&statistics(&vent_rows))
+}
+
+/// The largest coordinate either way, which keeps the arithmetic on segments well within an isize.
+const LIMIT: isize = 1 << 30;
+
+/// Check that every line is a horizontal, vertical or diagonal segment such as `0,9 -> 5,9`.
+pub fn validate(input: &str) -> Result<(), Vec<Diagnostic>> {
+    check_each_line(input, |validator, i, line| {
+        let ends = match ends(line) {
+            Some(ends) => ends,
+            None => return validator.error(Some(i), "expected a segment such as '0,9 -> 5,9'"),
+        };
+        let mut v = Vec::new();
+        for (x, y) in &ends {
+            for coordinate in &[x, y] {
+                if let Some(c) = validator.parse::<isize>(i, coordinate, "coordinate") {
+                    if validator.in_range(i, c, -LIMIT..=LIMIT, "coordinate") {
+                        v.push(c);
+                    }
+                }
+            }
+        }
+        if let [x0, y0, x1, y1] = v[..] {
+            validator.check(
+                is_straight(Vent { x: x0, y: y0 }, Vent { x: x1, y: y1 }),
+                Some(i),
+                "the segment is not horizontal, vertical or diagonal",
+            );
+        }
+    })
+}
+
+/// Generate `size` horizontal, vertical and diagonal segments on a 1000 by 1000 grid.
+pub fn generate(size: usize, seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+    let mut output = String::new();
+    for _ in 0..size {
+        let (x1, y1) = (rng.range(0..=999), rng.range(0..=999));
+        let (x2, y2) = match rng.below(3) {
+            0 => (rng.range(0..=999), y1),
+            1 => (x1, rng.range(0..=999)),
+            _ => {
+                // Walk diagonally for as far as the grid allows in a random direction.
+                let (dx, dy) = (*rng.choose(&[-1, 1]), *rng.choose(&[-1, 1]));
+                let room_x = if dx > 0 { 999 - x1 } else { x1 };
+                let room_y = if dy > 0 { 999 - y1 } else { y1 };
+                let length = rng.range(0..=room_x.min(room_y));
+                (x1 + dx * length, y1 + dy * length)
+            }
+        };
+        output.push_str(&format!("{},{} -> {},{}\n", x1, y1, x2, y2));
+    }
+    output
+}
+
+/// Parse the lines of vents.
+pub fn parse(input: &str) -> Result<Vec<VentRow>, Vec<Diagnostic>> {
+    validate(input)?;
+    let input = Input::new(input);
+    Ok(input
+        .lines()
+        .iter()
+        .map(|l| VentRow::from_str(l).unwrap())
+        .collect())
+}
+
+/// The number of points where lines overlap, without and with the diagonal lines.
+pub fn solve(input: &str) -> Result<(usize, usize), Vec<Diagnostic>> {
+    let vent_rows = parse(input)?;
+    Ok((
+        count_overlaps(&vent_rows, false),
+        count_overlaps(&vent_rows, true),
+    ))
 }
 
 pub fn day_05() -> (usize, usize) {
-    let raw_inputs = parse_lines("day_05".to_string());
-    (solve(&raw_inputs, false), solve(&raw_inputs, true))
+    solve(&read_file("day_05".to_string())).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day_05::{
+        count_overlaps, coverage, overlapping_vents, parse, solve, statistics, validate,
+        Orientation, Vent, VentRow,
+    };
+    use aoc::fuzz::check_solver;
+    use aoc::random::Rng;
+    use std::collections::HashMap;
+
+    const EXAMPLE: &str =
+        "0,9 -> 5,9\n8,0 -> 0,8\n9,4 -> 3,4\n2,2 -> 2,1\n7,0 -> 7,4\n6,4 -> 2,0\n\
+         0,9 -> 2,9\n3,4 -> 1,4\n0,0 -> 8,8\n5,5 -> 8,2";
+
+    /// Count the overlaps by marking every vent.
+    fn mark_all(vent_rows: &[VentRow], diagonals: bool) -> Vec<Vent> {
+        let mut covered = HashMap::new();
+        for row in vent_rows {
+            if !diagonals && row.is_diagonal() {
+                continue;
+            }
+            for vent in row.vents() {
+                *covered.entry(vent).or_insert(0) += 1;
+            }
+        }
+        let mut vents = covered
+            .into_iter()
+            .filter(|(_, c)| *c >= 2)
+            .map(|(v, _)| v)
+            .collect::<Vec<Vent>>();
+        vents.sort_unstable();
+        vents
+    }
+
+    #[test]
+    fn example() {
+        assert_eq!(solve(EXAMPLE), Ok((5, 12)));
+        let vents = overlapping_vents(&parse(EXAMPLE).unwrap(), false);
+        assert_eq!(vents[0], Vent { x: 0, y: 9 });
+        assert_eq!(vents.len(), 5);
+    }
+
+    #[test]
+    fn example_coverage() {
+        let vent_rows = parse(EXAMPLE).unwrap();
+        let coverage = coverage(&vent_rows, true);
+        assert_eq!(coverage.origin, Vent { x: 0, y: 0 });
+        assert_eq!(
+            coverage.to_text(),
+            "1.1....11.\n.111...2..\n..2.1.111.\n...1.2.2..\n.112313211\n\
+             ...1.2....\n..1...1...\n.1.....1..\n1.......1.\n222111....\n"
+        );
+        assert_eq!(coverage.heatmap()[4][4], 255);
+        assert_eq!(coverage.heatmap()[0][1], 0);
+
+        assert_eq!(vent_rows[1].orientation(), Orientation::AntiDiagonal);
+        assert_eq!(vent_rows[1].length(), 9);
+        let statistics = statistics(&vent_rows);
+        assert!(statistics.starts_with("0,9 -> 5,9: Horizontal, 6 vents\n"));
+        assert!(statistics.ends_with("AntiDiagonal: 2 rows, 13 vents\n"));
+    }
+
+    #[test]
+    fn matches_marking_every_vent() {
+        let mut rng = Rng::new(5);
+        for _ in 0..200 {
+            // A small field, so that rows overlap and cross in every possible way.
+            let vent_rows = (0..rng.range(1..=12))
+                .map(|_| {
+                    let start = Vent {
+                        x: rng.range(0..=9),
+                        y: rng.range(0..=9),
+                    };
+                    let length = rng.range(0..=6);
+                    let (dx, dy) = *rng.choose(&[(1, 0), (0, 1), (1, 1), (1, -1), (-1, 0)]);
+                    VentRow {
+                        start,
+                        end: Vent {
+                            x: start.x + dx * length,
+                            y: start.y + dy * length,
+                        },
+                    }
+                })
+                .collect::<Vec<VentRow>>();
+            for diagonals in [false, true] {
+                let expected = mark_all(&vent_rows, diagonals);
+                assert_eq!(count_overlaps(&vent_rows, diagonals), expected.len());
+                assert_eq!(overlapping_vents(&vent_rows, diagonals), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn large_coordinates() {
+        let vent_rows = parse(
+            "0,0 -> 4000000,4000000\n0,4000000 -> 4000000,0\n\
+             0,2000000 -> 4000000,2000000\n1000000,2000000 -> 3000000,2000000",
+        )
+        .unwrap();
+        assert_eq!(count_overlaps(&vent_rows, false), 2000001);
+        assert_eq!(count_overlaps(&vent_rows, true), 2000001);
+    }
+
+    #[test]
+    fn rejects_extreme_coordinates() {
+        assert!(validate("0,0 -> 9223372036854775807,0").is_err());
+        assert!(validate("-9223372036854775807,0 -> 9223372036854775807,0").is_err());
+        assert!(validate("0,-1073741824 -> 0,1073741824").is_ok());
+        // A sign does not separate the coordinates of an end.
+        assert!(validate("0,1 -> 3-2").is_err());
+    }
+
+    // Inputs that once crashed the solution.
+    const REGRESSIONS: &[&str] = &[
+        "0,0 -> 9223372036854775807,0",
+        "-9223372036854775807,0 -> 9223372036854775807,0",
+        "0,1 -> 3-2",
+    ];
+
+    #[test]
+    fn fuzz_solver() {
+        check_solver(5, 2000, &[EXAMPLE], REGRESSIONS, solve);
+    }
 }
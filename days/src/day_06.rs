@@ -1,40 +1,144 @@
-use aoc::parse_ints;
-use std::collections::HashMap;
+use aoc::input::Input;
+use aoc::matrix::Matrix;
+use aoc::matrix::Ring;
+use aoc::random::Rng;
+use aoc::read_file;
+use aoc::validate::check_lines;
+use aoc::validate::Diagnostic;
 
-fn solve(inputs: &Vec<isize>, duration: isize) -> usize {
-    let mut fish = HashMap::new();
+/// The timers of the lanternfish life cycle: after spawning a fish's timer is reset to `reset`,
+/// and a new fish starts at `spawn`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Lifecycle {
+    pub reset: usize,
+    pub spawn: usize,
+}
+
+impl Default for Lifecycle {
+    fn default() -> Self {
+        Lifecycle { reset: 6, spawn: 8 }
+    }
+}
 
-    for input in inputs {
-        let count = fish.entry(*input).or_insert(0);
-        *count += 1;
+/// The number of fish after `days`, counted in any ring: `usize` while it fits, `ModInt` for a
+/// count modulo a number and `BigUint` for the exact count. Raises the transition matrix to the
+/// number of days, so a horizon such as 10^12 days takes about 40 matrix products.
+pub fn count_fish<T: Ring>(timers: &[usize], days: u64, lifecycle: Lifecycle) -> T {
+    let size = timers
+        .iter()
+        .copied()
+        .chain([lifecycle.reset, lifecycle.spawn])
+        .max()
+        .unwrap()
+        + 1;
+    let mut cohorts = vec![T::zero(); size];
+    for &timer in timers {
+        cohorts[timer] = cohorts[timer].clone() + T::one();
     }
 
-    for _ in 0..duration {
-        let mut new_fish = HashMap::new();
-        for key in 0..=8 {
-            let fish_count = fish.entry(key).or_insert(0).clone();
-
-            // Move cohorts around.
-            if key == 0 {
-                // Parents are reset to 6.
-                let new_fish_count = new_fish.entry(6).or_insert(0);
-                *new_fish_count += fish_count;
-
-                // Children are set to 8.
-                let new_fish_count = new_fish.entry(8).or_insert(0);
-                *new_fish_count += fish_count;
-            } else {
-                let new_fish_count = new_fish.entry(key - 1).or_insert(0);
-                *new_fish_count += fish_count;
+    // Every day a cohort moves down one timer value, the fish at 0 are reset and spawn new fish.
+    let mut transition = Matrix::zeros(size);
+    for timer in 1..size {
+        transition[(timer - 1, timer)] = T::one();
+    }
+    transition[(lifecycle.reset, 0)] = T::one();
+    transition[(lifecycle.spawn, 0)] = transition[(lifecycle.spawn, 0)].clone() + T::one();
+
+    transition
+        .pow(days)
+        .mul_vector(&cohorts)
+        .into_iter()
+        .fold(T::zero(), |total, count| total + count)
+}
+
+/// Check that the input is a single line of timers between 0 and 8.
+pub fn validate(input: &str) -> Result<(), Vec<Diagnostic>> {
+    check_lines(input, |validator, lines| {
+        if validator.line_count(lines, 1..=1) {
+            for timer in lines[0].split(',') {
+                if let Some(timer) = validator.parse::<usize>(0, timer, "timer") {
+                    validator.in_range(0, timer, 0..=8, "timer");
+                }
             }
         }
-        fish = new_fish;
-    }
+    })
+}
 
-    fish.values().sum()
+/// Generate `size` fish with timers between 1 and 5, like the puzzle inputs.
+pub fn generate(size: usize, seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+    let timers = (0..size)
+        .map(|_| rng.range(1..=5).to_string())
+        .collect::<Vec<String>>();
+    format!("{}\n", timers.join(","))
+}
+
+/// Parse the timers of the fish.
+pub fn parse(input: &str) -> Result<Vec<usize>, Vec<Diagnostic>> {
+    validate(input)?;
+    let input = Input::new(input);
+    Ok(input.lines()[0]
+        .split(',')
+        .map(|t| t.parse().unwrap())
+        .collect())
+}
+
+/// The number of fish after 80 days and after 256 days.
+pub fn solve(input: &str) -> Result<(usize, usize), Vec<Diagnostic>> {
+    let timers = parse(input)?;
+    let lifecycle = Lifecycle::default();
+    Ok((
+        count_fish(&timers, 80, lifecycle),
+        count_fish(&timers, 256, lifecycle),
+    ))
 }
 
 pub fn day_06() -> (usize, usize) {
-    let inputs = parse_ints("day_06".to_string());
-    (solve(&inputs, 80), solve(&inputs, 256))
+    solve(&read_file("day_06".to_string())).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day_06::{count_fish, solve, Lifecycle};
+    use aoc::big_uint::BigUint;
+    use aoc::fuzz::check_solver;
+    use aoc::matrix::ModInt;
+
+    const EXAMPLE: [usize; 5] = [3, 4, 3, 1, 2];
+
+    #[test]
+    fn example() {
+        assert_eq!(solve("3,4,3,1,2"), Ok((5934, 26984457539)));
+        let lifecycle = Lifecycle::default();
+        assert_eq!(count_fish::<usize>(&EXAMPLE, 18, lifecycle), 26);
+        assert_eq!(
+            count_fish::<BigUint>(&EXAMPLE, 256, lifecycle).to_string(),
+            "26984457539"
+        );
+    }
+
+    #[test]
+    fn far_horizons() {
+        let lifecycle = Lifecycle::default();
+        assert_eq!(
+            count_fish::<BigUint>(&EXAMPLE, 1000, lifecycle).to_string(),
+            "379589061144698259131825683795505058481"
+        );
+        let modular: ModInt<1_000_000_007> = count_fish(&EXAMPLE, 1_000_000_000_000, lifecycle);
+        assert_eq!(modular.value(), 995077479);
+    }
+
+    #[test]
+    fn other_lifecycles() {
+        // With both timers at 0 every fish doubles every day.
+        let doubling = Lifecycle { reset: 0, spawn: 0 };
+        assert_eq!(count_fish::<usize>(&[0, 0, 0], 10, doubling), 3 * 1024);
+        let lifecycle = Lifecycle { reset: 1, spawn: 2 };
+        assert_eq!(count_fish::<u64>(&[0], 3, lifecycle), 3);
+    }
+
+    #[test]
+    fn fuzz_solver() {
+        check_solver(6, 2000, &["3,4,3,1,2"], &[], solve);
+    }
 }
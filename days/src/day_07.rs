@@ -1,45 +1,195 @@
-use aoc::parse_ints;
+use aoc::input::Input;
+use aoc::number_theory::triangular;
+use aoc::random::Rng;
+use aoc::read_file;
+use aoc::validate::check_lines;
+use aoc::validate::Diagnostic;
 
-fn absolute_difference(value: isize, data: &Vec<isize>) -> isize {
-    data.iter().map(|p| (value - p).abs()).sum()
+/// Where the crabs line up, and the fuel it takes them to get there.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Alignment {
+    pub position: isize,
+    pub fuel: isize,
 }
 
-fn crab_difference(value: isize, data: &Vec<isize>) -> isize {
-    data.iter()
-        .map(|p| (0..=(value - p).abs()).sum::<isize>())
+/// The fuel all crabs need to reach `target`, where `cost` is the fuel a crab needs to move a
+/// distance.
+pub fn total_fuel<F: Fn(usize) -> isize>(positions: &[isize], target: isize, cost: F) -> isize {
+    positions
+        .iter()
+        .map(|p| cost((target - p).unsigned_abs()))
         .sum()
 }
 
-fn pick_best(this: isize, other: isize, data: &Vec<isize>, is_a: bool) -> isize {
-    if is_a {
-        if absolute_difference(this, data) > absolute_difference(other, data) {
-            return other;
-        } else {
-            return this;
-        }
-    } else {
-        if crab_difference(this, data) > crab_difference(other, data) {
-            return other;
+/// The cheapest of the `candidates`, the lowest position on a tie.
+fn cheapest<F, I>(positions: &[isize], candidates: I, cost: F) -> Alignment
+where
+    F: Fn(usize) -> isize,
+    I: IntoIterator<Item = isize>,
+{
+    candidates
+        .into_iter()
+        .map(|position| Alignment {
+            position,
+            fuel: total_fuel(positions, position, &cost),
+        })
+        .min_by_key(|a| (a.fuel, a.position))
+        .unwrap()
+}
+
+/// Align the crabs for any cost that is convex in the distance, such as a constant or an
+/// increasing cost per step. The total fuel is then convex in the target position, so a binary
+/// search on its slope finds the lowest optimal position in `O(n log range)`.
+pub fn align<F: Fn(usize) -> isize>(positions: &[isize], cost: F) -> Alignment {
+    let (mut low, mut high) = (
+        *positions.iter().min().unwrap(),
+        *positions.iter().max().unwrap(),
+    );
+    while low < high {
+        let middle = low + (high - low) / 2;
+        if total_fuel(positions, middle, &cost) <= total_fuel(positions, middle + 1, &cost) {
+            high = middle;
         } else {
-            return this;
+            low = middle + 1;
         }
     }
+    cheapest(positions, [low], cost)
 }
 
-fn solve(inputs: &Vec<isize>, is_a: bool) -> isize {
-    let best_position = (inputs.iter().min().unwrap().clone()
-        ..=inputs.iter().max().unwrap().clone())
-        .fold(inputs.iter().max().unwrap().clone(), |best, new| {
-            pick_best(best, new, inputs, is_a)
-        });
-    if is_a {
-        return absolute_difference(best_position, inputs);
-    } else {
-        return crab_difference(best_position, inputs);
-    }
+/// Align the crabs at a constant cost per step, the lower median is an optimal position.
+pub fn align_linear(positions: &[isize]) -> Alignment {
+    let mut sorted = positions.to_vec();
+    let middle = (sorted.len() - 1) / 2;
+    let (_, median, _) = sorted.select_nth_unstable(middle);
+    cheapest(positions, [*median], |d| d as isize)
+}
+
+/// Align the crabs at a cost that increases by one every step. The optimum lies within half a
+/// step of the mean, so only the positions around it are tried.
+pub fn align_triangular(positions: &[isize]) -> Alignment {
+    let mean = positions.iter().sum::<isize>() / positions.len() as isize;
+    cheapest(positions, mean - 1..=mean + 2, |d| triangular(d) as isize)
+}
+
+/// The largest position, which keeps the fuel of up to 2^32 crabs within an isize.
+const LIMIT: isize = 1 << 16;
+
+/// Check that the input is a single line of crab positions.
+pub fn validate(input: &str) -> Result<(), Vec<Diagnostic>> {
+    check_lines(input, |validator, lines| {
+        if validator.line_count(lines, 1..=1) {
+            for position in lines[0].split(',') {
+                if let Some(p) = validator.parse::<isize>(0, position, "position") {
+                    validator.in_range(0, p, 0..=LIMIT, "position");
+                }
+            }
+        }
+    })
+}
+
+/// Generate `size` crabs at positions up to twice the number of crabs, or the largest position.
+pub fn generate(size: usize, seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+    let positions = (0..size)
+        .map(|_| rng.below((2 * size).min(LIMIT as usize)).to_string())
+        .collect::<Vec<String>>();
+    format!("{}\n", positions.join(","))
+}
+
+/// Parse the horizontal positions of the crabs.
+pub fn parse(input: &str) -> Result<Vec<isize>, Vec<Diagnostic>> {
+    validate(input)?;
+    let input = Input::new(input);
+    input.lines()[0]
+        .split(',')
+        .map(|p| {
+            p.parse().map_err(|_| {
+                vec![Diagnostic {
+                    line: Some(1),
+                    message: format!("'{}' is not a position", p),
+                }]
+            })
+        })
+        .collect()
+}
+
+/// The least fuel needed to align the crabs, at a constant and at an increasing cost per step.
+pub fn solve(input: &str) -> Result<(isize, isize), Vec<Diagnostic>> {
+    let positions = parse(input)?;
+    Ok((
+        align_linear(&positions).fuel,
+        align_triangular(&positions).fuel,
+    ))
 }
 
 pub fn day_07() -> (usize, usize) {
-    let inputs = parse_ints("day_07".to_string());
-    (solve(&inputs, true) as usize, solve(&inputs, false) as usize)
+    let (a, b) = solve(&read_file("day_07".to_string())).unwrap();
+    (a as usize, b as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day_07::{
+        align, align_linear, align_triangular, cheapest, solve, validate, Alignment,
+    };
+    use aoc::fuzz::check_solver;
+    use aoc::number_theory::triangular;
+    use aoc::random::Rng;
+
+    const EXAMPLE: [isize; 10] = [16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
+
+    #[test]
+    fn example() {
+        assert_eq!(solve("16,1,2,0,4,2,7,1,2,14"), Ok((37, 168)));
+        assert_eq!(
+            align_linear(&EXAMPLE),
+            Alignment {
+                position: 2,
+                fuel: 37
+            }
+        );
+        assert_eq!(
+            align_triangular(&EXAMPLE),
+            Alignment {
+                position: 5,
+                fuel: 168
+            }
+        );
+        // A cost that grows with the square of the distance is convex as well.
+        assert_eq!(align(&EXAMPLE, |d| (d * d) as isize).position, 5);
+    }
+
+    #[test]
+    fn matches_trying_every_position() {
+        let mut rng = Rng::new(7);
+        for _ in 0..200 {
+            let positions = (0..rng.range(1..=20))
+                .map(|_| rng.range(0..=50))
+                .collect::<Vec<isize>>();
+            let every = 0..=50;
+            let increasing = |d| triangular(d) as isize;
+            let linear = cheapest(&positions, every.clone(), |d| d as isize);
+            let growing = cheapest(&positions, every, increasing);
+            assert_eq!(align_linear(&positions), linear);
+            assert_eq!(align(&positions, |d| d as isize), linear);
+            assert_eq!(align_triangular(&positions), growing);
+            assert_eq!(align(&positions, increasing), growing);
+        }
+    }
+
+    #[test]
+    fn rejects_far_positions() {
+        assert!(validate("0,65536").is_ok());
+        assert!(validate("0,65537").is_err());
+        assert!(validate("0,9223372036854775808").is_err());
+        assert_eq!(solve("0,65536").map(|(a, _)| a), Ok(65536));
+    }
+
+    // Inputs that once crashed the solution.
+    const REGRESSIONS: &[&str] = &["0,9223372036854775807", "0,9223372036854775808"];
+
+    #[test]
+    fn fuzz_solver() {
+        check_solver(7, 2000, &["16,1,2,0,4,2,7,1,2,14"], REGRESSIONS, solve);
+    }
 }
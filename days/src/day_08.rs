@@ -1,87 +1,72 @@
-use aoc::parse_lines;
+use aoc::input::Input;
+use aoc::random::Rng;
+use aoc::read_file;
+use aoc::validate::check_each_line;
+use aoc::validate::Diagnostic;
+use aoc::validate::Validator;
 use std::collections::HashSet;
+use std::fmt;
 use std::str::FromStr;
 
-fn find_codeset(samples: &Vec<HashSet<char>>) -> Vec<Vec<String>> {
-    let c1 = samples.iter().filter(|s| s.len() == 2).next().unwrap();
-    let c7 = samples.iter().filter(|s| s.len() == 3).next().unwrap();
-    let c4 = samples.iter().filter(|s| s.len() == 4).next().unwrap();
-    let c8 = samples.iter().filter(|s| s.len() == 7).next().unwrap();
-    // Length of 6 and `c4` is not a subset and has `c1` as a subset.
-    let c0 = samples
-        .iter()
-        .filter(|s| s.len() == 6 && !c4.is_subset(s) && c1.is_subset(s))
-        .next()
-        .unwrap();
-    // Length of 6 and has `c4` as a subset.
-    let c9 = samples
-        .iter()
-        .filter(|s| s.len() == 6 && c4.is_subset(s))
-        .next()
-        .unwrap();
-    // Length of 6 and does not have `c7` as a subset and does not have `c1` as a subset.
-    let c6 = samples
-        .iter()
-        .filter(|s| s.len() == 6 && !c7.is_subset(s) && !c1.is_subset(s))
-        .next()
-        .unwrap();
-    // Length of 5 and is a subset of `c9`.
-    let c2 = samples
-        .iter()
-        .filter(|s| s.len() == 5 && !s.is_subset(c9))
-        .next()
-        .unwrap();
-    // Length of 5 and is no a subset of `c9` and `c1` is not a subset.
-    let c5 = samples
-        .iter()
-        .filter(|s| s.len() == 5 && s.is_subset(c9) && !c1.is_subset(s))
-        .next()
-        .unwrap();
-    // Length of 5 and has `c1` as a subset.
-    let c3 = samples
-        .iter()
-        .filter(|s| s.len() == 5 && c1.is_subset(s))
-        .next()
-        .unwrap();
-
-    vec![
-        vec![c0.iter().collect::<String>(), "0".to_string()],
-        vec![c1.iter().collect::<String>(), "1".to_string()],
-        vec![c2.iter().collect::<String>(), "2".to_string()],
-        vec![c3.iter().collect::<String>(), "3".to_string()],
-        vec![c4.iter().collect::<String>(), "4".to_string()],
-        vec![c5.iter().collect::<String>(), "5".to_string()],
-        vec![c6.iter().collect::<String>(), "6".to_string()],
-        vec![c7.iter().collect::<String>(), "7".to_string()],
-        vec![c8.iter().collect::<String>(), "8".to_string()],
-        vec![c9.iter().collect::<String>(), "9".to_string()],
-    ]
-}
-
-fn decode(input: &HashSet<char>, samples: &Vec<HashSet<char>>) -> char {
-    let codeset: Vec<Vec<String>> = find_codeset(samples);
-    // Less efficient than a hashmap lookup, but only 800 entries are to be decoded.
-    for i in 0..10 {
-        let code_set: HashSet<char> = codeset.get(i).unwrap().get(0).unwrap().chars().collect();
-        if &code_set == input {
-            return codeset
-                .get(i)
-                .unwrap()
-                .get(1)
-                .unwrap()
-                .clone()
-                .chars()
-                .next()
-                .unwrap();
+/// The segments of a pattern as bits, `a` is the lowest.
+fn segments(pattern: &str) -> Result<u8, ()> {
+    pattern.chars().try_fold(0, |mask, c| match c {
+        'a'..='g' => Ok(mask | 1 << (c as u8 - b'a')),
+        _ => Err(()),
+    })
+}
+
+/// Every way to wire seven segments, `wiring[i]` is the segment that wire `i` drives.
+fn wirings() -> Vec<[u8; 7]> {
+    let mut wirings = Vec::with_capacity(5040);
+    let mut wiring = [0, 1, 2, 3, 4, 5, 6];
+    // Heap's algorithm, which swaps a single pair between consecutive permutations.
+    let mut counters = [0; 7];
+    wirings.push(wiring);
+    let mut i = 0;
+    while i < 7 {
+        if counters[i] < i {
+            wiring.swap(if i % 2 == 0 { 0 } else { counters[i] }, i);
+            wirings.push(wiring);
+            counters[i] += 1;
+            i = 0;
+        } else {
+            counters[i] = 0;
+            i += 1;
         }
     }
-    panic!("unknown code")
+    wirings
 }
 
+fn rewire(pattern: u8, wiring: &[u8; 7]) -> u8 {
+    (0..7)
+        .filter(|i| pattern & 1 << i != 0)
+        .fold(0, |mask, i| mask | 1 << wiring[i])
+}
+
+/// Why an entry could not be decoded.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DecodeError {
+    /// No wiring turns every pattern into a digit.
+    Inconsistent,
+    /// This many wirings fit the patterns, and they do not agree.
+    Ambiguous(usize),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::Inconsistent => write!(f, "no wiring matches every pattern"),
+            DecodeError::Ambiguous(n) => write!(f, "{} wirings match the patterns", n),
+        }
+    }
+}
+
+/// The unique signal patterns and the output digits of a display, as sets of segments.
 #[derive(Clone, Debug)]
-struct Entry {
-    signal_pattern: Vec<HashSet<char>>,
-    output: Vec<HashSet<char>>,
+pub struct Entry {
+    signal_pattern: Vec<u8>,
+    output: Vec<u8>,
 }
 
 impl FromStr for Entry {
@@ -91,55 +76,257 @@ impl FromStr for Entry {
         let mut split = input.split(" | ");
         let signal_pattern = split
             .next()
-            .unwrap()
-            .split(" ")
-            .map(|s| s.chars().collect::<HashSet<char>>())
-            .collect();
+            .ok_or(())?
+            .split(' ')
+            .map(segments)
+            .collect::<Result<Vec<u8>, ()>>()?;
         let output = split
             .next()
-            .unwrap()
-            .split(" ")
-            .map(|s| s.chars().collect::<HashSet<char>>())
-            .collect();
-        return Ok(Entry {
+            .ok_or(())?
+            .split(' ')
+            .map(segments)
+            .collect::<Result<Vec<u8>, ()>>()?;
+        Ok(Entry {
             signal_pattern,
             output,
-        });
+        })
     }
 }
 
-fn solve_a(entries: &Vec<Entry>) -> usize {
-    let filter: HashSet<usize> = [2, 3, 4, 7].iter().cloned().collect();
+impl Entry {
+    /// The wirings under which every pattern, including the output, shows a digit. Every pattern
+    /// narrows down the wirings that are left, so most are ruled out by the first few.
+    fn consistent_wirings(&self) -> Vec<[u8; 7]> {
+        let digits = SEGMENTS.map(|s| segments(s).unwrap());
+        let mut wirings = wirings();
+        for pattern in self.signal_pattern.iter().chain(&self.output) {
+            wirings.retain(|w| digits.contains(&rewire(*pattern, w)));
+        }
+        wirings
+    }
+
+    /// The segment every wire `a` to `g` drives, if the patterns pin it down.
+    pub fn wiring(&self) -> Result<[char; 7], DecodeError> {
+        match self.consistent_wirings()[..] {
+            [] => Err(DecodeError::Inconsistent),
+            [wiring] => Ok(wiring.map(|s| (b'a' + s) as char)),
+            ref wirings => Err(DecodeError::Ambiguous(wirings.len())),
+        }
+    }
+
+    /// The number on the output, which may be known even if the wiring is not.
+    pub fn decode(&self) -> Result<usize, DecodeError> {
+        let digits = SEGMENTS.map(|s| segments(s).unwrap());
+        let wirings = self.consistent_wirings();
+        let values = wirings
+            .iter()
+            .map(|w| {
+                self.output.iter().fold(0, |value, pattern| {
+                    let digit = digits.iter().position(|d| *d == rewire(*pattern, w));
+                    10 * value + digit.unwrap()
+                })
+            })
+            .collect::<HashSet<usize>>();
+        match values.len() {
+            0 => Err(DecodeError::Inconsistent),
+            1 => Ok(values.into_iter().next().unwrap()),
+            _ => Err(DecodeError::Ambiguous(wirings.len())),
+        }
+    }
+}
+
+fn solve_a(entries: &[Entry]) -> usize {
     entries
         .iter()
-        .map(|e| {
-            e.output
-                .iter()
-                .filter(|s| filter.contains(&s.len()))
-                .collect::<Vec<&HashSet<char>>>()
-                .len()
-        })
-        .sum()
+        .flat_map(|e| &e.output)
+        .filter(|s| [2, 3, 4, 7].contains(&s.count_ones()))
+        .count()
 }
 
-fn solve_b(entries: &Vec<Entry>) -> usize {
-    entries
+fn solve_b(entries: &[Entry]) -> Result<usize, Vec<Diagnostic>> {
+    let mut validator = Validator::new();
+    let mut sum = 0;
+    for (i, entry) in entries.iter().enumerate() {
+        match entry.decode() {
+            Ok(value) => sum += value,
+            Err(error) => validator.error(Some(i), &error.to_string()),
+        }
+    }
+    validator.finish().map(|_| sum)
+}
+
+/// Check that every entry has ten distinct patterns and four output digits, written as sets of
+/// the segments `a` to `g`.
+pub fn validate(input: &str) -> Result<(), Vec<Diagnostic>> {
+    check_each_line(input, |validator, i, line| {
+        if !validator.charset(i, line, "abcdefg |") {
+            return;
+        }
+        let parts = line.split(" | ").collect::<Vec<&str>>();
+        if !validator.check(
+            parts.len() == 2,
+            Some(i),
+            "expected patterns and output separated by ' | '",
+        ) {
+            return;
+        }
+        let patterns = parts[0].split(' ').collect::<Vec<&str>>();
+        let output = parts[1].split(' ').collect::<Vec<&str>>();
+        validator.check(
+            patterns.len() == 10,
+            Some(i),
+            &format!("expected 10 patterns, found {}", patterns.len()),
+        );
+        validator.check(
+            output.len() == 4,
+            Some(i),
+            &format!("expected 4 output digits, found {}", output.len()),
+        );
+        for pattern in patterns.iter().chain(output.iter()) {
+            let distinct = pattern.chars().collect::<HashSet<char>>();
+            validator.check(
+                segments(pattern).is_ok() && !pattern.is_empty() && distinct.len() == pattern.len(),
+                Some(i),
+                &format!("'{}' is not a set of segments", pattern),
+            );
+        }
+        let distinct = patterns
+            .iter()
+            .map(|p| {
+                let mut segments = p.chars().collect::<Vec<char>>();
+                segments.sort_unstable();
+                segments
+            })
+            .collect::<HashSet<Vec<char>>>();
+        validator.check(
+            distinct.len() == patterns.len(),
+            Some(i),
+            "the patterns are not distinct",
+        );
+    })
+}
+
+/// The segments of the digits 0 to 9 on an unscrambled display.
+const SEGMENTS: [&str; 10] = [
+    "abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "acf", "abcdefg", "abcdfg",
+];
+
+/// The pattern of a digit when segment `a` is wired to `wires[0]` and so on, in a random order.
+fn scramble(digit: usize, wires: &[char], rng: &mut Rng) -> String {
+    let mut pattern = SEGMENTS[digit]
+        .chars()
+        .map(|c| wires[c as usize - 'a' as usize])
+        .collect::<Vec<char>>();
+    rng.shuffle(&mut pattern);
+    pattern.into_iter().collect()
+}
+
+/// Generate `size` entries, each with its own scrambled wiring.
+pub fn generate(size: usize, seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+    let mut output = String::new();
+    for _ in 0..size {
+        let mut wires = "abcdefg".chars().collect::<Vec<char>>();
+        rng.shuffle(&mut wires);
+        let mut digits = (0..10).collect::<Vec<usize>>();
+        rng.shuffle(&mut digits);
+        digits.extend((0..4).map(|_| rng.below(10)));
+        let patterns = digits
+            .iter()
+            .map(|d| scramble(*d, &wires, &mut rng))
+            .collect::<Vec<String>>();
+        output.push_str(&format!(
+            "{} | {}\n",
+            patterns[..10].join(" "),
+            patterns[10..].join(" ")
+        ));
+    }
+    output
+}
+
+/// Parse the notes on the displays.
+pub fn parse(input: &str) -> Result<Vec<Entry>, Vec<Diagnostic>> {
+    validate(input)?;
+    let input = Input::new(input);
+    Ok(input
+        .lines()
         .iter()
-        .map(|e| {
-            e.output
-                .iter()
-                .map(|s| decode(s, &[&e.signal_pattern[..], &e.output[..]].concat()))
-                .collect::<String>()
-                .parse::<usize>()
-                .unwrap()
-        })
-        .sum()
+        .map(|l| Entry::from_str(l).unwrap())
+        .collect())
+}
+
+/// The number of output digits with a unique number of segments, and the sum of the outputs.
+pub fn solve(input: &str) -> Result<(usize, usize), Vec<Diagnostic>> {
+    let entries = parse(input)?;
+    Ok((solve_a(&entries), solve_b(&entries)?))
 }
 
 pub fn day_08() -> (usize, usize) {
-    let entries = parse_lines("day_08".to_string())
-        .iter()
-        .map(|s| Entry::from_str(s).unwrap())
-        .collect::<Vec<Entry>>();
-    (solve_a(&entries), solve_b(&entries))
+    solve(&read_file("day_08".to_string())).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day_08::{solve, validate, wirings, DecodeError, Entry};
+    use aoc::fuzz::check_parser;
+    use aoc::fuzz::check_solver;
+    use aoc::input::Input;
+    use aoc::validate::Diagnostic;
+    use std::collections::HashSet;
+    use std::str::FromStr;
+
+    const EXAMPLE: &str =
+        "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf";
+
+    #[test]
+    fn example() {
+        assert_eq!(wirings().iter().collect::<HashSet<_>>().len(), 5040);
+        let entry = Entry::from_str(EXAMPLE).unwrap();
+        assert_eq!(entry.wiring(), Ok(['c', 'f', 'g', 'a', 'b', 'd', 'e']));
+        assert_eq!(entry.decode(), Ok(5353));
+        assert_eq!(solve(EXAMPLE), Ok((0, 5353)));
+    }
+
+    #[test]
+    fn partial_and_broken_entries() {
+        // A 1 and a 7 leave the order of the other segments open, but the output is still known.
+        let entry = Entry::from_str("ab dab | ba abd").unwrap();
+        assert_eq!(entry.decode(), Ok(17));
+        assert_eq!(entry.wiring(), Err(DecodeError::Ambiguous(2 * 24)));
+
+        // Two wires can only show a 1, so `ab` cannot be both 1 and part of a 4 without `a` or `b`.
+        let entry = Entry::from_str("ab cdeg | ab").unwrap();
+        assert_eq!(entry.decode(), Err(DecodeError::Inconsistent));
+
+        let entry = Entry::from_str("ab abcd | cd").unwrap();
+        assert_eq!(entry.decode(), Err(DecodeError::Inconsistent));
+        let broken = "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb ab\n\
+                      acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cd";
+        assert_eq!(
+            solve(broken),
+            Err(vec![Diagnostic {
+                line: Some(2),
+                message: "no wiring matches every pattern".to_string()
+            }])
+        );
+    }
+
+    // Inputs that once crashed the parser, or passed validation but failed to parse.
+    const REGRESSIONS: &[&str] = &["c", "fb c g f |b d cb a b ab | b b b a"];
+
+    #[test]
+    fn fuzz_parser() {
+        let parses = |input: &str| {
+            Input::new(input)
+                .lines()
+                .iter()
+                .all(|l| Entry::from_str(l).is_ok())
+        };
+        check_parser(8, 2000, &[EXAMPLE], REGRESSIONS, validate, parses);
+    }
+
+    #[test]
+    fn fuzz_solver() {
+        check_solver(8, 2000, &[EXAMPLE], REGRESSIONS, solve);
+    }
 }
@@ -1,75 +1,318 @@
-use aoc::parse_u32_map;
-use std::collections::HashMap;
-use std::collections::HashSet;
+use aoc::disjoint_set::DisjointSet;
+use aoc::input::Input;
+use aoc::random::Rng;
+use aoc::read_file;
+use aoc::render::grid_to_text;
+use aoc::validate::check_lines;
+use aoc::validate::Diagnostic;
+use std::collections::VecDeque;
 
-fn is_low_point(i: i32, j: i32, map: &HashMap<(i32, i32), u32>) -> bool {
-    for deltas in vec![(0, -1), (0, 1), (-1, 0), (1, 0)] {
-        if map.get(&(i + deltas.0, j + deltas.1)).unwrap_or(&&u32::MAX) <= map.get(&(i, j)).unwrap()
-        {
-            return false;
+/// A height map stored row by row, a map of width 0 has no cells.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HeightMap {
+    pub width: usize,
+    pub heights: Vec<u8>,
+}
+
+impl HeightMap {
+    fn cells(&self) -> usize {
+        if self.width == 0 {
+            0
+        } else {
+            self.heights.len()
         }
     }
-    true
+
+    /// The cells left, right, above and below `cell` that are on the map.
+    fn neighbours(&self, cell: usize) -> impl Iterator<Item = usize> {
+        let (width, cells) = (self.width, self.cells());
+        let column = cell % width;
+        IntoIterator::into_iter([
+            (column > 0).then(|| cell - 1),
+            (column + 1 < width).then(|| cell + 1),
+            cell.checked_sub(width),
+            Some(cell + width).filter(|c| *c < cells),
+        ])
+        .flatten()
+    }
+
+    /// The cells lower than all of their neighbours, in reading order.
+    pub fn low_points(&self) -> Vec<usize> {
+        (0..self.cells())
+            .filter(|&c| {
+                self.neighbours(c)
+                    .all(|n| self.heights[n] > self.heights[c])
+            })
+            .collect()
+    }
 }
 
-fn solve_a(map: &HashMap<(i32, i32), u32>) -> u32 {
-    map.keys()
-        .filter(|k| is_low_point(k.0, k.1, map))
-        .map(|k| *map.get(k).unwrap() + 1)
-        .sum()
+/// A basin, and its lowest cell as row and column. On a tie the first cell in reading order is
+/// the low point.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Basin {
+    pub low_point: (usize, usize),
+    pub size: usize,
 }
 
-fn flood_fill(
-    k: &(i32, i32),
-    basin_map: &mut HashMap<(i32, i32), i32>,
-    map: &HashMap<(i32, i32), u32>,
-    basin_identifier: i32,
-) {
-    if basin_map.contains_key(k) {
-        return;
-    } else if *map.get(k).unwrap_or(&&9) == 9 {
-        basin_map.insert(*k, i32::MAX);
-    } else {
-        basin_map.insert(*k, basin_identifier);
-        for deltas in vec![(0, -1), (0, 1), (-1, 0), (1, 0)] {
-            if map.contains_key(k) {
-                flood_fill(
-                    &(k.0 + deltas.0, k.1 + deltas.1),
-                    basin_map,
-                    map,
-                    basin_identifier,
-                );
+/// The basin every cell belongs to, ridges of height 9 belong to none.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Basins {
+    pub width: usize,
+    pub labels: Vec<Option<usize>>,
+    pub basins: Vec<Basin>,
+}
+
+impl Basins {
+    /// Ridges as `#` and every basin as a letter, which repeats after 52 basins.
+    pub fn to_text(&self) -> String {
+        let letters = ('a'..='z').chain('A'..='Z').collect::<Vec<char>>();
+        let rows = self
+            .labels
+            .chunks(self.width.max(1))
+            .map(|r| r.to_vec())
+            .collect::<Vec<Vec<Option<usize>>>>();
+        grid_to_text(&rows, |l| l.map_or('#', |b| letters[b % letters.len()]))
+    }
+}
+
+/// Label the basins, numbered in the reading order of their first cell. Neighbouring cells that
+/// are not ridges are merged in a disjoint set, which needs no recursion or queue however large a
+/// basin is, and a second pass numbers the sets.
+pub fn label_basins(map: &HeightMap) -> Basins {
+    let cells = map.cells();
+    let mut sets = DisjointSet::new(cells);
+    for cell in 0..cells {
+        if map.heights[cell] == 9 {
+            continue;
+        }
+        for neighbour in map.neighbours(cell).filter(|n| *n > cell) {
+            if map.heights[neighbour] != 9 {
+                sets.union(cell, neighbour);
             }
         }
     }
+
+    let mut label_of_root = vec![None; cells];
+    let mut labels = vec![None; cells];
+    let mut basins: Vec<Basin> = Vec::new();
+    let mut lowest: Vec<usize> = Vec::new();
+    for cell in (0..cells).filter(|c| map.heights[*c] != 9) {
+        let root = sets.find(cell);
+        let label = *label_of_root[root].get_or_insert_with(|| {
+            basins.push(Basin {
+                low_point: (cell / map.width, cell % map.width),
+                size: 0,
+            });
+            lowest.push(cell);
+            basins.len() - 1
+        });
+        labels[cell] = Some(label);
+        basins[label].size += 1;
+        if map.heights[cell] < map.heights[lowest[label]] {
+            lowest[label] = cell;
+            basins[label].low_point = (cell / map.width, cell % map.width);
+        }
+    }
+    Basins {
+        width: map.width,
+        labels,
+        basins,
+    }
+}
+
+fn solve_a(map: &HeightMap) -> usize {
+    map.low_points()
+        .iter()
+        .map(|c| map.heights[*c] as usize + 1)
+        .sum()
 }
 
-fn solve_b(map: &HashMap<(i32, i32), u32>) -> usize {
-    let mut basin_map: HashMap<(i32, i32), i32> = HashMap::new();
-    let mut basin_identifiers = 0..10000;
+/// The product of the sizes of the three largest basins, or of all basins if there are fewer.
+fn solve_b(map: &HeightMap) -> usize {
+    let mut basin_sizes = label_basins(map)
+        .basins
+        .iter()
+        .map(|b| b.size)
+        .collect::<Vec<usize>>();
+    basin_sizes.sort_unstable_by(|a, b| b.cmp(a));
+    basin_sizes.iter().take(3).product()
+}
 
-    for k in map.keys() {
-        flood_fill(k, &mut basin_map, map, basin_identifiers.next().unwrap())
+/// Check that the heightmap is a rectangular grid of digits.
+pub fn validate(input: &str) -> Result<(), Vec<Diagnostic>> {
+    check_lines(input, |validator, lines| {
+        validator.grid(lines, 0);
+        for (i, line) in lines.iter().enumerate() {
+            validator.charset(i, line, "0123456789");
+        }
+    })
+}
+
+/// Generate a `size` by `size` height map. Basins grow from random low points, with heights rising
+/// by one per step up to 8, and are separated by ridges of height 9. Maps of only a few cells may
+/// end up with fewer than the three basins the second part needs.
+pub fn generate(size: usize, seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+    let cells = size * size;
+    let neighbours = |cell: usize| {
+        let (x, y) = (cell % size, cell / size);
+        let mut neighbours = Vec::new();
+        if x + 1 < size {
+            neighbours.push(cell + 1);
+        }
+        if y + 1 < size {
+            neighbours.push(cell + size);
+        }
+        if x > 0 {
+            neighbours.push(cell - 1);
+        }
+        if y > 0 {
+            neighbours.push(cell - size);
+        }
+        neighbours
+    };
+
+    // Grow all basins at once, so every cell joins the basin of its nearest low point.
+    let mut basins = vec![None; cells];
+    let mut heights = vec![0; cells];
+    let mut queue = VecDeque::new();
+    let mut low_points = (0..cells).collect::<Vec<usize>>();
+    rng.shuffle(&mut low_points);
+    for (basin, cell) in low_points.iter().take((cells / 40).max(3)).enumerate() {
+        basins[*cell] = Some(basin);
+        queue.push_back(*cell);
+    }
+    while let Some(cell) = queue.pop_front() {
+        for neighbour in neighbours(cell) {
+            if basins[neighbour].is_none() {
+                basins[neighbour] = basins[cell];
+                heights[neighbour] = (heights[cell] + 1).min(8);
+                queue.push_back(neighbour);
+            }
+        }
     }
 
-    let mut basin_sizes: Vec<usize> = basin_map
-        .values()
-        .collect::<HashSet<&i32>>()
-        .iter()
-        .map(|b| {
-            basin_map
-                .values()
-                .filter(|v| v == b && **v != i32::MAX)
-                .count()
+    // Raise a ridge wherever a cell borders another basin to the right or below.
+    for cell in 0..cells {
+        if neighbours(cell)
+            .iter()
+            .filter(|n| **n > cell)
+            .any(|n| basins[*n] != basins[cell])
+        {
+            heights[cell] = 9;
+        }
+    }
+    heights
+        .chunks(size.max(1))
+        .map(|row| {
+            let row = row.iter().map(|h| h.to_string());
+            format!("{}\n", row.collect::<String>())
         })
-        .collect::<Vec<usize>>();
-    basin_sizes.sort();
-    basin_sizes[(basin_sizes.len() - 3)..]
-        .iter()
-        .fold(1, |a, b| a * b)
+        .collect()
+}
+
+/// Parse the heightmap.
+pub fn parse(input: &str) -> Result<HeightMap, Vec<Diagnostic>> {
+    validate(input)?;
+    let input = Input::new(input);
+    let lines = input.lines();
+    Ok(HeightMap {
+        width: lines[0].len(),
+        heights: lines.concat().bytes().map(|b| b - b'0').collect(),
+    })
+}
+
+/// The sum of the risk levels of the low points, and the product of the three largest basins.
+pub fn solve(input: &str) -> Result<(usize, usize), Vec<Diagnostic>> {
+    let map = parse(input)?;
+    Ok((solve_a(&map), solve_b(&map)))
 }
 
 pub fn day_09() -> (usize, usize) {
-    let map = parse_u32_map("day_09".to_string());
-    (solve_a(&map) as usize, solve_b(&map) as usize)
+    solve(&read_file("day_09".to_string())).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day_09::{label_basins, parse, solve, Basin, HeightMap};
+    use aoc::fuzz::check_solver;
+
+    const EXAMPLE: &str = "2199943210\n3987894921\n9856789892\n8767896789\n9899965678";
+
+    #[test]
+    fn example() {
+        assert_eq!(solve(EXAMPLE), Ok((15, 1134)));
+        let map = parse(EXAMPLE).unwrap();
+        assert_eq!(map.low_points(), vec![1, 9, 22, 46]);
+        let basins = label_basins(&map);
+        assert_eq!(
+            basins.basins,
+            vec![
+                Basin {
+                    low_point: (0, 1),
+                    size: 3
+                },
+                Basin {
+                    low_point: (0, 9),
+                    size: 9
+                },
+                Basin {
+                    low_point: (2, 2),
+                    size: 14
+                },
+                Basin {
+                    low_point: (4, 6),
+                    size: 9
+                },
+            ]
+        );
+        assert_eq!(basins.labels[11], None);
+        assert_eq!(basins.labels[12], Some(2));
+        assert_eq!(
+            basins.to_text(),
+            "aa###bbbbb\na#ccc#b#bb\n#ccccc#d#b\nccccc#ddd#\n#c###ddddd\n"
+        );
+    }
+
+    #[test]
+    fn few_basins() {
+        assert_eq!(solve("919\n999"), Ok((2, 1)));
+        assert_eq!(solve("9"), Ok((10, 1)));
+        assert_eq!(solve("1\n9\n0"), Ok((3, 1)));
+        let empty = HeightMap {
+            width: 0,
+            heights: vec![1, 2],
+        };
+        assert_eq!(label_basins(&empty).basins, vec![]);
+        assert_eq!(label_basins(&empty).to_text(), "");
+        assert_eq!(empty.low_points(), vec![]);
+    }
+
+    #[test]
+    fn millions_of_cells() {
+        // Every other row is a ridge with a gap at alternating ends, so that a single basin winds
+        // through the whole map.
+        let (width, height) = (2000, 1500);
+        let heights = (0..width * height)
+            .map(|c| {
+                let (row, column) = (c / width, c % width);
+                let gap = if row % 4 == 1 { width - 1 } else { 0 };
+                if row % 2 == 1 && column != gap {
+                    9
+                } else {
+                    (c % 9) as u8
+                }
+            })
+            .collect::<Vec<u8>>();
+        let basins = label_basins(&HeightMap { width, heights });
+        assert_eq!(basins.basins.len(), 1);
+        assert_eq!(basins.basins[0].size, width * height / 2 + height / 2);
+        assert_eq!(basins.basins[0].low_point, (0, 0));
+    }
+
+    #[test]
+    fn fuzz_solver() {
+        check_solver(9, 2000, &[EXAMPLE], &[], solve);
+    }
 }
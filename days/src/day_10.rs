@@ -1,4 +1,8 @@
-use aoc::parse_lines;
+use aoc::input::Input;
+use aoc::random::Rng;
+use aoc::read_file;
+use aoc::validate::check_lines;
+use aoc::validate::Diagnostic;
 use std::collections::VecDeque;
 
 const OPENS: [char; 4] = ['<', '{', '[', '('];
@@ -59,7 +63,129 @@ fn solve_b(lines: &Vec<String>) -> isize {
     values.get(values.len() / 2).unwrap() * -1
 }
 
+/// The most chunks an incomplete line may leave open, as completion scores grow fivefold with
+/// every chunk and have to stay within an `isize`.
+const MAX_OPEN: usize = 27;
+
+/// Check that every line consists of brackets, never closes a chunk that was not opened, and
+/// that at least one line is incomplete.
+pub fn validate(input: &str) -> Result<(), Vec<Diagnostic>> {
+    check_lines(input, |validator, lines| {
+        validator.non_empty(lines);
+        let mut incomplete = false;
+        for (i, line) in lines.iter().enumerate() {
+            if !validator.charset(i, line, "()[]{}<>") {
+                continue;
+            }
+            let mut open = Vec::new();
+            let mut corrupted = false;
+            for (column, c) in line.chars().enumerate() {
+                if let Some(kind) = OPENS.iter().position(|o| *o == c) {
+                    open.push(kind);
+                } else if open.is_empty() {
+                    validator.error(
+                        Some(i),
+                        &format!("'{}' in column {} closes nothing", c, column + 1),
+                    );
+                    corrupted = true;
+                    break;
+                } else if open.pop() != CLOSES.iter().position(|close| *close == c) {
+                    corrupted = true;
+                    break;
+                }
+            }
+            if !corrupted && !open.is_empty() {
+                incomplete = true;
+                validator.check(
+                    open.len() <= MAX_OPEN,
+                    Some(i),
+                    &format!(
+                        "{} chunks are left open, more than {}",
+                        open.len(),
+                        MAX_OPEN
+                    ),
+                );
+            }
+        }
+        validator.check(incomplete, None, "no line is incomplete");
+    })
+}
+
+/// A random chunk sequence that is left incomplete, or corrupted by a wrong closing character.
+/// Chunks are nested at most 20 deep, which keeps completion scores within an `isize`.
+fn random_line(rng: &mut Rng, corrupted: bool) -> String {
+    let mut line = String::new();
+    let mut open = Vec::new();
+    for _ in 0..rng.range(10..=100) {
+        if open.is_empty() || (open.len() < 20 && rng.chance(0.5)) {
+            open.push(rng.below(4));
+            line.push(OPENS[*open.last().unwrap()]);
+        } else {
+            line.push(CLOSES[open.pop().unwrap()]);
+        }
+    }
+    if open.is_empty() {
+        open.push(rng.below(4));
+        line.push(OPENS[*open.last().unwrap()]);
+    }
+    if corrupted {
+        let wrong = (open.last().unwrap() + 1 + rng.below(3)) % 4;
+        line.push(CLOSES[wrong]);
+    }
+    line
+}
+
+/// Generate `size` lines, about half of them corrupted. The first line is always incomplete.
+pub fn generate(size: usize, seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+    (0..size)
+        .map(|i| {
+            let corrupted = i > 0 && rng.chance(0.5);
+            format!("{}\n", random_line(&mut rng, corrupted))
+        })
+        .collect()
+}
+
+/// Parse the lines of the navigation subsystem.
+pub fn parse(input: &str) -> Result<Vec<String>, Vec<Diagnostic>> {
+    validate(input)?;
+    let input = Input::new(input);
+    Ok(input.lines().iter().map(|l| l.to_string()).collect())
+}
+
+/// The syntax error score of the corrupted lines, and the middle completion score of the
+/// incomplete lines.
+pub fn solve(input: &str) -> Result<(isize, isize), Vec<Diagnostic>> {
+    let lines = parse(input)?;
+    Ok((solve_a(&lines), solve_b(&lines)))
+}
+
 pub fn day_10() -> (usize, usize) {
-    let input = parse_lines("day_10".to_string());
-    (solve_a(&input) as usize, solve_b(&input) as usize)
+    let (a, b) = solve(&read_file("day_10".to_string())).unwrap();
+    (a as usize, b as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day_10::solve;
+    use aoc::fuzz::check_solver;
+
+    const EXAMPLE: &str = "[({(<(())[]>[[{[]{<()<>>\n\
+         [(()[<>])]({[<{<<[]>>(\n\
+         {([(<{}[<>[]}>{[]{[(<()>\n\
+         (((({<>}<{<{<>}{[]{[]{}\n\
+         [[<[([]))<([[{}[[()]]]\n\
+         [{[{({}]{}}([{[{{{}}([]\n\
+         {<[[]]>}<{[{[{[]{()[[[]\n\
+         [<(<(<(<{}))><([]([]()\n\
+         <{([([[(<>()){}]>(<<{{\n\
+         <{([{{}}[<[[[<>{}]]]>[]]";
+
+    // Inputs that once crashed the solution.
+    const REGRESSIONS: &[&str] = &["(>(", "((((((((((((((((((((((((((((((((("];
+
+    #[test]
+    fn fuzz_solver() {
+        check_solver(10, 2000, &[EXAMPLE], REGRESSIONS, solve);
+    }
 }
@@ -1,68 +1,173 @@
-use aoc::parse_u32_map;
-use std::collections::HashMap;
+use aoc::automaton::Automaton;
+use aoc::automaton::Boundary;
+use aoc::automaton::Neighbourhood;
+use aoc::input::Input;
+use aoc::random::Rng;
+use aoc::read_file;
+use aoc::validate::check_lines;
+use aoc::validate::Diagnostic;
 use std::collections::HashSet;
 
-fn flash(k: &(i32, i32), old: &mut HashMap<(i32, i32), u32>, flashes: &mut HashSet<(i32, i32)>) {
-    old.insert(*k, 0);
-    flashes.insert(k.clone());
-    for d in vec![
-        (0, -1),
-        (0, 1),
-        (-1, 0),
-        (1, 0),
-        (-1, -1),
-        (1, 1),
-        (-1, 1),
-        (1, -1),
-    ] {
-        let dk = (k.0 + d.0, k.1 + d.1);
-        if !flashes.contains(&dk) && old.contains_key(&dk) {
-            old.insert(dk, *old.get(&dk).unwrap() + 1);
+// Octopodes that flashed during this step are `None`, they are fixed to value `0` until the step
+// is over. An octopus flashes once its energy goes over 9, raising the energy of its neighbours.
+fn cascade(octopus: &Option<u32>, neighbours: &[Option<u32>]) -> Option<u32> {
+    match octopus {
+        Some(energy) if *energy <= 9 => {
+            let flashing = neighbours.iter().filter(|n| n.is_some_and(|e| e > 9));
+            Some(energy + flashing.count() as u32)
         }
+        _ => None,
     }
 }
 
-fn do_step(old: &mut HashMap<(i32, i32), u32>) -> usize {
-    let keys = old.keys().map(|v| v.clone()).collect::<Vec<(i32, i32)>>();
-
+fn do_step(octopodes: &mut Automaton<Option<u32>>) -> usize {
     // First, the energy level of each octopus increases by 1.
-    for k in &keys {
-        old.insert(*k, *old.get(k).unwrap() + 1);
-    }
+    octopodes.step(|o, _| o.map(|e| e + 1));
+
+    // Handle flashes until nothing can flash anymore, then reset the ones that flashed.
+    octopodes.run_to_fixpoint(cascade);
+    let flashes = octopodes.count(|o| o.is_none());
+    octopodes.step(|o, _| Some(o.unwrap_or(0)));
+
+    flashes
+}
+
+fn octopodes(levels: &[Vec<u32>]) -> Automaton<Option<u32>> {
+    let rows = levels
+        .iter()
+        .map(|row| row.iter().map(|e| Some(*e)).collect())
+        .collect();
+    // Outside the grid there are no octopodes, which reads the same as one that never flashes.
+    Automaton::new(rows, Neighbourhood::Moore, Boundary::Bounded(Some(0)))
+}
 
-    // Handle flashes until nothing can flash anymore. Octopodes can only flash once per turn, then
-    // are fixed to value `0`.
-    let mut flashes = HashSet::new();
-    let mut old_flash_count = usize::MAX;
-    while old_flash_count != flashes.len() {
-        old_flash_count = flashes.len();
-
-        for k in &keys {
-            if *old.get(k).unwrap() > 9 {
-                flash(k, old, &mut flashes);
-            }
+/// The number of flashes during the first `steps` steps.
+fn count_flashes(levels: &[Vec<u32>], steps: usize) -> usize {
+    let mut octopodes = octopodes(levels);
+    (0..steps).map(|_| do_step(&mut octopodes)).sum()
+}
+
+/// The first step during which all octopodes flash. Energy levels stay within 0 to 9, so the grid
+/// repeats itself eventually, and `None` means it did so before all octopodes flashed at once.
+fn synchronize(levels: &[Vec<u32>]) -> Option<usize> {
+    let mut octopodes = octopodes(levels);
+    let size = octopodes.width() * octopodes.height();
+    let mut seen = HashSet::new();
+    let mut step = 0;
+    while seen.insert(octopodes.rows()) {
+        step += 1;
+        if do_step(&mut octopodes) == size {
+            return Some(step);
         }
     }
-
-    flashes.len()
+    None
 }
 
-fn solve(part_a: bool) -> usize {
-    let mut current_state = parse_u32_map("day_11".to_string());
+/// Check that the energy levels form a rectangular grid of digits.
+pub fn validate(input: &str) -> Result<(), Vec<Diagnostic>> {
+    check_lines(input, |validator, lines| {
+        validator.grid(lines, 0);
+        for (i, line) in lines.iter().enumerate() {
+            validator.charset(i, line, "0123456789");
+        }
+    })
+}
 
-    let mut flashes = 0;
-    let mut i = 0;
-    while i < 100 || !part_a {
-        i += 1;
-        let df = do_step(&mut current_state);
-        if df == 100 {
-            return i;
+/// Generate a `size` by `size` grid of energy levels. Grids are drawn again until the octopodes
+/// first all flash at once after step 100 but within 1000 steps, like the puzzle inputs. Tiny grids
+/// always synchronize early and larger grids than the puzzle's rarely at all, so after a number of
+/// draws the levels stay at 5 or below and any grid that synchronizes within 1000 steps will do.
+pub fn generate(size: usize, seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+    let late_draws = if size <= 10 { 100 } else { 0 };
+    let mut draws = 0;
+    loop {
+        let late = draws < late_draws;
+        draws += 1;
+        let levels = (0..size)
+            .map(|_| {
+                (0..size)
+                    .map(|_| rng.below(if late { 10 } else { 6 }) as u32)
+                    .collect()
+            })
+            .collect::<Vec<Vec<u32>>>();
+        let mut octopodes = octopodes(&levels);
+        let first = (1..=1000).find(|_| do_step(&mut octopodes) == size * size);
+        if first.map_or(false, |step| step > 100 || !late) {
+            return levels
+                .iter()
+                .map(|row| {
+                    let row = row.iter().map(|e| e.to_string());
+                    format!("{}\n", row.collect::<String>())
+                })
+                .collect();
         }
-        flashes += df;
     }
-    return flashes;
+}
+
+/// Parse the energy levels of the octopodes.
+pub fn parse(input: &str) -> Result<Vec<Vec<u32>>, Vec<Diagnostic>> {
+    validate(input)?;
+    let input = Input::new(input);
+    Ok(input
+        .lines()
+        .iter()
+        .map(|l| l.chars().map(|c| c.to_digit(10).unwrap()).collect())
+        .collect())
+}
+
+/// The number of flashes after 100 steps, and the first step during which all octopodes flash.
+pub fn solve(input: &str) -> Result<(usize, usize), Vec<Diagnostic>> {
+    let levels = parse(input)?;
+    let synchronized = synchronize(&levels).ok_or_else(|| {
+        vec![Diagnostic {
+            line: None,
+            message: "the octopodes never all flash at once".to_string(),
+        }]
+    })?;
+    Ok((count_flashes(&levels, 100), synchronized))
 }
 
 pub fn day_11() -> (usize, usize) {
-    (solve(true), solve(false))
+    solve(&read_file("day_11".to_string())).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day_11::{count_flashes, generate, parse, solve};
+    use aoc::fuzz::check_solver;
+
+    const EXAMPLE: &str = "5483143223\n2745854711\n5264556173\n6141336146\n6357385478\n\
+         4167524645\n2176841721\n6882881134\n4846848554\n5283751526";
+
+    #[test]
+    fn example() {
+        assert_eq!(count_flashes(&parse(EXAMPLE).unwrap(), 10), 204);
+        assert_eq!(solve(EXAMPLE), Ok((1656, 195)));
+    }
+
+    #[test]
+    fn generated_grids_synchronize_after_step_100() {
+        for seed in 0..3 {
+            let (_, synchronized) = solve(&generate(10, seed)).unwrap();
+            assert!((101..=1000).contains(&synchronized));
+        }
+    }
+
+    #[test]
+    fn grids_that_never_synchronize() {
+        // Each octopus flashes one step after the other, and pushes the other on a step further.
+        assert_eq!(
+            solve("02").unwrap_err()[0].message,
+            "the octopodes never all flash at once"
+        );
+    }
+
+    // Inputs that once hung the solution.
+    const REGRESSIONS: &[&str] = &["02"];
+
+    #[test]
+    fn fuzz_solver() {
+        check_solver(11, 1000, &[EXAMPLE], REGRESSIONS, solve);
+    }
 }
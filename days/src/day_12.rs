@@ -1,61 +1,180 @@
-use aoc::parse_lines;
-use std::collections::HashMap;
+use aoc::graph::Graph;
+use aoc::random::Rng;
+use aoc::read_file;
+use aoc::validate::check_lines;
+use aoc::validate::Diagnostic;
 use std::collections::HashSet;
 
-fn parse_input() -> HashMap<String, HashSet<String>> {
-    let mut map: HashMap<String, HashSet<String>> = HashMap::new();
-    let lines = parse_lines("day_12".to_string());
-    for line in lines {
-        let split_line = line
-            .split("-")
-            .map(|v| v.to_string())
-            .collect::<Vec<String>>();
-
-        let first = split_line.get(0).unwrap();
-        let second = split_line.get(1).unwrap();
-
-        map.entry(first.clone()).or_default().insert(second.clone());
-        map.entry(second.clone()).or_default().insert(first.clone());
-    }
-    return map;
-}
-
-fn find_paths(
-    route: &Vec<&String>,
-    input: &HashMap<String, HashSet<String>>,
-    allow_single_double: bool,
-) -> usize {
-    let current = route.last().unwrap();
-    if **current == "end".to_string() {
-        return 1;
-    }
-
-    let mut paths = 0;
-    for next in input.get(*current).unwrap() {
-        let is_start = *next == "start".to_string();
-        let is_upper = next.to_uppercase() == *next;
-        if is_upper || !route.contains(&next) || (allow_single_double && !is_start) {
-            let mut allow_another_double = allow_single_double;
-            if !is_upper && route.contains(&next) {
-                allow_another_double = false;
+fn count_paths(input: &Graph, allow_single_double: bool) -> usize {
+    let start = input.id("start").unwrap();
+    let end = input.id("end").unwrap();
+
+    // Big caves can be entered any number of times, small caves once and the start never again.
+    let limit = |id: usize| {
+        let name = input.name(id);
+        if id == start {
+            0
+        } else if is_big(name) {
+            usize::MAX
+        } else {
+            1
+        }
+    };
+    input.count_paths(start, end, limit, if allow_single_double { 1 } else { 0 })
+}
+
+/// Big caves are written in capitals.
+fn is_big(cave: &str) -> bool {
+    cave.to_uppercase() == cave
+}
+
+/// Check that every line connects two named caves, and that there is a start and an end. Two big
+/// caves may not be connected, as there would be no end to the paths going back and forth.
+pub fn validate(input: &str) -> Result<(), Vec<Diagnostic>> {
+    check_lines(input, |validator, lines| {
+        let mut caves = HashSet::new();
+        validator.non_empty(lines);
+        for (i, line) in lines.iter().enumerate() {
+            match line.split('-').collect::<Vec<&str>>()[..] {
+                [a, b] if !a.is_empty() && !b.is_empty() => {
+                    validator.charset(
+                        i,
+                        line,
+                        "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ-",
+                    );
+                    validator.check(
+                        !(is_big(a) && is_big(b)),
+                        Some(i),
+                        "two big caves are connected",
+                    );
+                    caves.insert(a);
+                    caves.insert(b);
+                }
+                _ => validator.error(Some(i), "expected two caves such as 'start-A'"),
             }
+        }
+        for cave in &["start", "end"] {
+            validator.check(
+                caves.contains(cave),
+                None,
+                &format!("there is no '{}' cave", cave),
+            );
+        }
+    })
+}
 
-            let mut cloned_vec = route.clone();
-            cloned_vec.push(next);
-            let result = find_paths(&cloned_vec, input, allow_another_double);
-            paths += result;
+/// Generate a cave system of `size` caves besides the start and the end. About a third of the
+/// caves are big, and big caves are never connected to each other, so the number of paths is
+/// finite, although it grows very quickly with `size`. Names are two letters long, or longer when
+/// there would not be enough names to go round.
+pub fn generate(size: usize, seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+    let mut length = 2;
+    while 26usize.pow(length) < 2 * size {
+        length += 1;
+    }
+    let mut caves = vec!["start".to_string()];
+    let mut names = caves.iter().cloned().collect::<HashSet<String>>();
+    names.insert("end".to_string());
+    while caves.len() <= size {
+        let big = rng.chance(1.0 / 3.0);
+        let letters = if big { b'A' } else { b'a' };
+        let name = (0..length)
+            .map(|_| (letters + rng.below(26) as u8) as char)
+            .collect::<String>();
+        if names.insert(name.clone()) {
+            caves.push(name);
+        }
+    }
+
+    // Attach every cave to one before it, so that all of them can be reached from the start,
+    // then add the end and a few more tunnels.
+    let mut tunnels = HashSet::new();
+    for i in 1..caves.len() {
+        let options = (0..i)
+            .filter(|j| !(is_big(&caves[i]) && is_big(&caves[*j])))
+            .collect::<Vec<usize>>();
+        tunnels.insert((*rng.choose(&options), i));
+    }
+    caves.push("end".to_string());
+    let end = caves.len() - 1;
+    for _ in 0..2 {
+        let cave = if end > 1 { 1 + rng.below(end - 1) } else { 0 };
+        tunnels.insert((cave, end));
+    }
+    for _ in 0..size / 2 {
+        let (a, b) = (1 + rng.below(end), 1 + rng.below(end));
+        if a < b && !(is_big(&caves[a]) && is_big(&caves[b])) {
+            tunnels.insert((a, b));
         }
     }
 
-    return paths;
+    let mut tunnels = tunnels.into_iter().collect::<Vec<(usize, usize)>>();
+    // The order of a set is not reproducible, so sort before shuffling.
+    tunnels.sort_unstable();
+    rng.shuffle(&mut tunnels);
+    tunnels
+        .iter()
+        .map(|(a, b)| format!("{}-{}\n", caves[*a], caves[*b]))
+        .collect()
 }
 
-fn solve(input: &HashMap<String, HashSet<String>>, allow_single_double: bool) -> usize {
-    let start = "start".to_string();
-    find_paths(&vec![&start], input, allow_single_double)
+/// Parse the map of the caves.
+pub fn parse(input: &str) -> Result<Graph, Vec<Diagnostic>> {
+    validate(input)?;
+    Ok(Graph::parse(input, false).unwrap())
+}
+
+/// The number of paths visiting small caves at most once, and when one of them may be visited
+/// twice.
+pub fn solve(input: &str) -> Result<(usize, usize), Vec<Diagnostic>> {
+    let caves = parse(input)?;
+    Ok((count_paths(&caves, false), count_paths(&caves, true)))
 }
 
 pub fn day_12() -> (usize, usize) {
-    let input = parse_input();
-    (solve(&input, false), solve(&input, true))
+    solve(&read_file("day_12".to_string())).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day_12::{generate, solve, validate};
+    use aoc::fuzz::check_solver;
+
+    const EXAMPLES: [&str; 3] = [
+        "start-A\nstart-b\nA-c\nA-b\nb-d\nA-end\nb-end",
+        "dc-end\nHN-start\nstart-kj\ndc-start\ndc-HN\nLN-dc\nHN-end\nkj-sl\nkj-HN\nkj-dc",
+        "fs-end\nhe-DX\nfs-he\nstart-DX\npj-DX\nend-zg\nzg-sl\nzg-pj\npj-he\nRW-he\nfs-DX\n\
+         pj-RW\nzg-RW\nstart-pj\nhe-WI\nzg-he\npj-fs\nstart-RW",
+    ];
+
+    #[test]
+    fn examples() {
+        assert_eq!(solve(EXAMPLES[0]), Ok((10, 36)));
+        assert_eq!(solve(EXAMPLES[1]), Ok((19, 103)));
+        assert_eq!(solve(EXAMPLES[2]), Ok((226, 3509)));
+    }
+
+    #[test]
+    fn rejects_connected_big_caves() {
+        let errors = validate("start-A\nA-BC\nBC-end").unwrap_err();
+        assert_eq!(errors[0].line, Some(2));
+        assert!(validate("start-A\nA-b\nb-end").is_ok());
+    }
+
+    #[test]
+    fn generates_more_caves_than_two_letters_can_name() {
+        let caves = generate(2000, 12);
+        assert!(validate(&caves).is_ok());
+        let names = caves.lines().flat_map(|l| l.split('-'));
+        assert!(names.filter(|c| *c != "end").any(|c| c.len() == 3));
+    }
+
+    // Inputs that once hung the solution.
+    const REGRESSIONS: &[&str] = &["start-A\nA-B\nB-end"];
+
+    #[test]
+    fn fuzz_solver() {
+        check_solver(12, 1000, &EXAMPLES, REGRESSIONS, solve);
+    }
 }
@@ -1,69 +1,238 @@
-use aoc::binary_grid_from_string;
-use aoc::ints_from_str;
+use aoc::input::Input;
+use aoc::random::Rng;
 use aoc::read_file;
+use aoc::render::invalid_input;
+use aoc::render::points_to_rows;
+use aoc::render::points_to_text;
+use aoc::render::FrameWriter;
+use aoc::validate::check_lines;
+use aoc::validate::sections;
+use aoc::validate::Diagnostic;
 use std::collections::HashSet;
+use std::io;
+use std::path::Path;
 
-fn pretty_print(set: &HashSet<(isize, isize)>) {
-    let max_x = set.iter().map(|t| t.0).max().unwrap();
-    let max_y = set.iter().map(|t| t.1).max().unwrap();
-
-    for i in 0..=max_y {
-        let mut line = String::new();
-        for j in 0..=max_x {
-            if set.contains(&(j, i)) {
-                line += &"#";
-            } else {
-                line += &" ";
-            }
-        }
-        println!("{}", line);
-    }
+/// The dots on the transparent paper.
+pub type Paper = HashSet<(isize, isize)>;
+
+/// A fold of the paper along a vertical line `x = position` or a horizontal line `y = position`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Fold {
+    pub position: isize,
+    pub along_x: bool,
 }
 
-fn do_fold(set: &mut HashSet<(isize, isize)>, fold: &isize, is_x: &bool) {
-    let cloned_set: HashSet<(isize, isize)> = set.clone();
+fn do_fold(set: &mut Paper, fold: &Fold) {
+    let cloned_set: Paper = set.clone();
     for t in cloned_set {
         set.remove(&t);
         let mut x = t.0;
         let mut y = t.1;
+        let position = fold.position;
 
-        if *is_x && x > *fold {
-            x = fold - (x - fold);
-        } else if !*is_x && y > *fold {
-            y = fold - (y - fold);
+        if fold.along_x && x > position {
+            x = position - (x - position);
+        } else if !fold.along_x && y > position {
+            y = position - (y - position);
         }
         set.insert((x, y));
     }
 }
 
-fn solve(input: &String, part_a: bool) -> usize {
-    let mut i = input.split("\n\n");
-    let mut grid = binary_grid_from_string(&i.next().unwrap().to_string());
+fn read_instructions(input: &Input) -> Result<(Paper, Vec<Fold>), ()> {
+    let sections = input.sections();
+    let (dots, folds) = match &sections[..] {
+        [dots, folds] => (dots, folds),
+        _ => return Err(()),
+    };
+    let paper = dots
+        .lines()
+        .iter()
+        .map(|l| match l.split(',').collect::<Vec<&str>>()[..] {
+            [x, y] => Ok((x.parse().map_err(|_| ())?, y.parse().map_err(|_| ())?)),
+            _ => Err(()),
+        })
+        .collect::<Result<Paper, ()>>()?;
+    let folds = folds
+        .lines()
+        .iter()
+        .map(|l| {
+            let (axis, position) = l
+                .strip_prefix("fold along ")
+                .and_then(|f| f.split_once('='))
+                .ok_or(())?;
+            Ok(Fold {
+                position: position.parse().map_err(|_| ())?,
+                along_x: axis == "x",
+            })
+        })
+        .collect::<Result<Vec<Fold>, ()>>()?;
+    Ok((paper, folds))
+}
+
+/// Write the paper before and after every fold as bitmaps, and the folded code as text.
+pub fn render(input: &str, directory: &Path) -> io::Result<()> {
+    let (mut grid, folds) = parse(input).map_err(invalid_input)?;
+    let mut frames = FrameWriter::new(directory, "day_13")?;
 
-    let raw_folds = i.next().unwrap().to_string();
-    let folds = ints_from_str(&raw_folds);
-    let is_x_folds = raw_folds
-        .split("\n")
-        .map(|l| l.contains("x"))
-        .collect::<Vec<bool>>();
+    frames.write_pbm(&points_to_rows(&grid))?;
+    for fold in &folds {
+        do_fold(&mut grid, fold);
+        frames.write_pbm(&points_to_rows(&grid))?;
+    }
+    frames.write_text(&points_to_text(&grid, '#', ' '))
+}
 
-    for index in 0..folds.len() {
-        let fold = folds.get(index).unwrap();
-        let is_x = is_x_folds.get(index).unwrap();
+/// The largest coordinate of a dot or fold, which keeps the drawn paper small.
+const LIMIT: isize = 1 << 12;
 
-        do_fold(&mut grid, fold, is_x);
+/// Check that a list of dots is followed by a list of folds along either axis.
+pub fn validate(input: &str) -> Result<(), Vec<Diagnostic>> {
+    check_lines(input, |validator, lines| {
+        let sections = sections(lines);
+        if !validator.check(
+            sections.len() == 2,
+            None,
+            &format!(
+                "expected dots and folds separated by a blank line, found {} sections",
+                sections.len()
+            ),
+        ) {
+            return;
+        }
 
-        if part_a {
-            return grid.len();
+        let (start, dots) = &sections[0];
+        for (i, dot) in dots.iter().enumerate() {
+            match dot.split(',').collect::<Vec<&str>>()[..] {
+                [x, y] => {
+                    for coordinate in &[x, y] {
+                        if let Some(c) =
+                            validator.parse::<isize>(start + i, coordinate, "coordinate")
+                        {
+                            validator.in_range(start + i, c, 0..=LIMIT, "coordinate");
+                        }
+                    }
+                }
+                _ => validator.error(Some(start + i), "expected a dot such as '6,10'"),
+            }
+        }
+        let (start, folds) = &sections[1];
+        for (i, fold) in folds.iter().enumerate() {
+            match fold
+                .strip_prefix("fold along x=")
+                .or_else(|| fold.strip_prefix("fold along y="))
+            {
+                Some(position) => {
+                    if let Some(p) = validator.parse::<isize>(start + i, position, "fold position")
+                    {
+                        validator.in_range(start + i, p, 0..=LIMIT, "fold position");
+                    }
+                }
+                None => {
+                    validator.error(Some(start + i), "expected a fold such as 'fold along y=7'")
+                }
+            }
         }
+    })
+}
+
+/// The folds of a generated sheet, which fold it down to 40 by 6 like the puzzle inputs.
+const FOLDS: [(char, isize); 12] = [
+    ('x', 655),
+    ('y', 447),
+    ('x', 327),
+    ('y', 223),
+    ('x', 163),
+    ('y', 111),
+    ('x', 81),
+    ('y', 55),
+    ('x', 40),
+    ('y', 27),
+    ('y', 13),
+    ('y', 6),
+];
+
+/// Generate `size` dots on a 1311 by 895 sheet. Each dot is placed on the folded sheet first and
+/// then unfolded to either side of every fold, so that no dot ends up on a fold line.
+pub fn generate(size: usize, seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+    let mut output = String::new();
+    for _ in 0..size {
+        let (mut x, mut y) = (rng.range(0..=39), rng.range(0..=5));
+        for (axis, position) in FOLDS.iter().rev() {
+            let coordinate = if *axis == 'x' { &mut x } else { &mut y };
+            if rng.chance(0.5) {
+                *coordinate = 2 * position - *coordinate;
+            }
+        }
+        output.push_str(&format!("{},{}\n", x, y));
+    }
+    output.push('\n');
+    for (axis, position) in &FOLDS {
+        output.push_str(&format!("fold along {}={}\n", axis, position));
     }
-    if false {
-        pretty_print(&grid);
+    output
+}
+
+/// Parse the dots on the paper and the folds.
+pub fn parse(input: &str) -> Result<(Paper, Vec<Fold>), Vec<Diagnostic>> {
+    validate(input)?;
+    read_instructions(&Input::new(input)).map_err(|_| {
+        vec![Diagnostic {
+            line: None,
+            message: "the dots or folds are malformed".to_string(),
+        }]
+    })
+}
+
+/// The number of dots after the first fold, and the code that appears after all folds.
+pub fn solve(input: &str) -> Result<(usize, String), Vec<Diagnostic>> {
+    let (mut grid, folds) = parse(input)?;
+    let mut after_first_fold = 0;
+    for (i, fold) in folds.iter().enumerate() {
+        do_fold(&mut grid, fold);
+        if i == 0 {
+            after_first_fold = grid.len();
+        }
     }
-    0
+    Ok((after_first_fold, points_to_text(&grid, '#', ' ')))
 }
 
 pub fn day_13() -> (usize, usize) {
-    let input = read_file("day_13".to_string());
-    (solve(&input, true), solve(&input, false))
+    (solve(&read_file("day_13".to_string())).unwrap().0, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day_13::{render, solve};
+    use aoc::fuzz::check_solver;
+    use std::env;
+    use std::fs;
+
+    const EXAMPLE: &str = "6,10\n0,14\n9,10\n0,3\n10,4\n4,11\n6,0\n6,12\n4,1\n0,13\n10,12\n3,4\n\
+         3,0\n8,4\n1,10\n2,14\n8,10\n9,0\n\nfold along y=7\nfold along x=5";
+
+    #[test]
+    fn renders_every_fold() {
+        let directory = env::temp_dir().join("day_13_renders_every_fold");
+        render(EXAMPLE, &directory).unwrap();
+        // The unfolded paper and one bitmap per fold, followed by the code.
+        assert_eq!(fs::read_dir(&directory).unwrap().count(), 4);
+        let first = fs::read_to_string(directory.join("day_13_00000.pbm")).unwrap();
+        assert!(first.starts_with("P1\n11 15\n"));
+        let last = fs::read_to_string(directory.join("day_13_00002.pbm")).unwrap();
+        assert!(last.starts_with("P1\n5 5\n"));
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    // Inputs that once crashed the solution.
+    const REGRESSIONS: &[&str] = &[
+        "9223372036854775808,0\n\nfold along y=7",
+        "1,1\n\nfold along x=99999999999999999999",
+    ];
+
+    #[test]
+    fn fuzz_solver() {
+        check_solver(13, 1000, &[EXAMPLE], REGRESSIONS, solve);
+    }
 }
@@ -1,92 +1,165 @@
+use aoc::counter::Counter;
+use aoc::input::Input;
+use aoc::matrix::Matrix;
+use aoc::random::Rng;
 use aoc::read_file;
+use aoc::validate::check_lines;
+use aoc::validate::sections;
+use aoc::validate::Diagnostic;
 use std::collections::HashMap;
 
-fn parse_rules(input: &String) -> HashMap<(char, char), char> {
+/// Pair insertion rules, from a pair of elements to the element inserted between them.
+pub type Rules = HashMap<(char, char), char>;
+
+fn parse_rules(input: &Input) -> Rules {
     let mut rules = HashMap::new();
-    for line in input.split("\n") {
+    for line in input.lines() {
         let mut split = line.chars();
         rules.insert(
-            (split.nth(0).unwrap(), split.nth(0).unwrap()),
+            (split.next().unwrap(), split.next().unwrap()),
             split.nth(4).unwrap(),
         );
     }
     rules
 }
 
-fn solve(template: &String, rules: &HashMap<(char, char), char>, part_a: bool) -> usize {
+fn polymerize(template: &str, rules: &Rules, part_a: bool) -> usize {
     let mut current_string = template.chars().collect::<Vec<char>>();
     current_string.push('x'); // mark the tail
 
-    let mut window_count: HashMap<(char, char), usize> = HashMap::new();
-    current_string.windows(2).for_each(|w| {
-        let t = (w[0], w[1]);
-        match window_count.get_mut(&t) {
-            Some(v) => *v += 1,
-            _ => {
-                window_count.insert(t, 1);
+    let window_count: Counter<(char, char)> =
+        current_string.windows(2).map(|w| (w[0], w[1])).collect();
+
+    // Number every window that can occur, starting from the template and following the rules.
+    let mut windows: Vec<(char, char)> = window_count.iter().map(|(t, _)| *t).collect();
+    let mut index: HashMap<(char, char), usize> = HashMap::new();
+    let mut i = 0;
+    while i < windows.len() {
+        let t = windows[i];
+        index.insert(t, i);
+        if let Some(v) = rules.get(&t) {
+            for new_t in [(t.0, *v), (*v, t.1)].iter().copied() {
+                if !windows.contains(&new_t) {
+                    windows.push(new_t);
+                }
+            }
+        }
+        i += 1;
+    }
+
+    // A window with a rule becomes two windows, other windows stay as they are.
+    let mut transition = Matrix::zeros(windows.len());
+    for (t, i) in &index {
+        match rules.get(t) {
+            Some(v) => {
+                transition[(index[&(t.0, *v)], *i)] += 1;
+                transition[(index[&(*v, t.1)], *i)] += 1;
             }
+            _ => transition[(*i, *i)] += 1,
         };
-    });
+    }
 
-    for i in 0..40 {
-        if i == 10 && part_a {
-            break;
+    let counts = windows
+        .iter()
+        .map(|t| window_count.get(t))
+        .collect::<Vec<usize>>();
+    let steps = if part_a { 10 } else { 40 };
+    let counts = transition.pow(steps).mul_vector(&counts);
+
+    let mut frequencies: Counter<char> = Counter::new();
+    for (t, c) in windows.iter().zip(counts).filter(|(_, c)| *c > 0) {
+        frequencies.add(t.0, c);
+    }
+
+    frequencies.max().unwrap().1 - frequencies.min().unwrap().1
+}
+
+/// Check that the polymer template is followed by pair insertion rules such as `CH -> B`.
+pub fn validate(input: &str) -> Result<(), Vec<Diagnostic>> {
+    check_lines(input, |validator, lines| {
+        let sections = sections(lines);
+        if !validator.check(
+            sections.len() == 2,
+            None,
+            &format!(
+                "expected a template and rules separated by a blank line, found {} sections",
+                sections.len()
+            ),
+        ) {
+            return;
         }
 
-        let mut new_window_count: HashMap<(char, char), usize> = HashMap::new();
-
-        for (t, c) in window_count {
-            match rules.get(&t) {
-                Some(v) => {
-                    let t1 = (t.0, *v);
-                    let t2 = (*v, t.1);
-                    match new_window_count.get_mut(&t1) {
-                        Some(v) => *v += c,
-                        _ => {
-                            new_window_count.insert(t1, c);
-                        }
-                    };
-                    match new_window_count.get_mut(&t2) {
-                        Some(v) => *v += c,
-                        _ => {
-                            new_window_count.insert(t2, c);
-                        }
-                    };
-                }
-                _ => {
-                    match new_window_count.get_mut(&t) {
-                        Some(v) => *v += c,
-                        _ => {
-                            new_window_count.insert(t, c);
-                        }
-                    };
+        let (start, template) = &sections[0];
+        validator.check(
+            template.len() == 1,
+            Some(*start),
+            "the template should be on a single line",
+        );
+        validator.charset(*start, template[0], "ABCDEFGHIJKLMNOPQRSTUVWXYZ");
+        let (start, rules) = &sections[1];
+        for (i, rule) in rules.iter().enumerate() {
+            match rule.split(" -> ").collect::<Vec<&str>>()[..] {
+                [pair, element] if pair.len() == 2 && element.len() == 1 => {
+                    validator.charset(start + i, rule, "ABCDEFGHIJKLMNOPQRSTUVWXYZ ->");
                 }
-            };
+                _ => validator.error(Some(start + i), "expected a rule such as 'CH -> B'"),
+            }
         }
+    })
+}
 
-        window_count = new_window_count;
+/// Generate a template of `size` elements, with a rule for every pair of the ten elements the
+/// puzzle inputs use.
+pub fn generate(size: usize, seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+    let elements = "BCFHKNOPSV".chars().collect::<Vec<char>>();
+    let template = (0..size)
+        .map(|_| *rng.choose(&elements))
+        .collect::<String>();
+    let mut output = format!("{}\n\n", template);
+    for a in &elements {
+        for b in &elements {
+            output.push_str(&format!("{}{} -> {}\n", a, b, rng.choose(&elements)));
+        }
     }
+    output
+}
 
-    let mut frequencies: HashMap<char, usize> = HashMap::new();
-    for (t, c) in window_count {
-        match frequencies.get_mut(&t.0) {
-            Some(v) => *v += c,
-            _ => {
-                frequencies.insert(t.0, c);
-            }
-        };
-    }
+/// Parse the polymer template and the pair insertion rules.
+pub fn parse(input: &str) -> Result<(String, Rules), Vec<Diagnostic>> {
+    validate(input)?;
+    let sections = Input::new(input).sections();
+    Ok((sections[0].to_string(), parse_rules(&sections[1])))
+}
 
-    frequencies.values().max().unwrap() - frequencies.values().min().unwrap()
+/// The difference between the most and the least common element after 10 and after 40 steps.
+pub fn solve(input: &str) -> Result<(usize, usize), Vec<Diagnostic>> {
+    let (template, rules) = parse(input)?;
+    Ok((
+        polymerize(&template, &rules, true),
+        polymerize(&template, &rules, false),
+    ))
 }
 
 pub fn day_14() -> (usize, usize) {
-    let file = read_file("day_14".to_string());
-    let mut file_split = file.split("\n\n");
-    let template = file_split.next().unwrap().to_string();
-    let rules = parse_rules(&file_split.next().unwrap().to_string());
-    (
-        solve(&template, &rules, true),
-        solve(&template, &rules, false),
-    )
+    solve(&read_file("day_14".to_string())).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day_14::solve;
+    use aoc::fuzz::check_solver;
+
+    const EXAMPLE: &str = "NNCB\n\nCH -> B\nHH -> N\nCB -> H\nNH -> C\nHB -> C\nHC -> B\nHN -> C\n\
+         NN -> C\nBH -> H\nNC -> B\nNB -> B\nBN -> B\nBB -> N\nBC -> B\nCC -> N\nCN -> C";
+
+    #[test]
+    fn example() {
+        assert_eq!(solve(EXAMPLE), Ok((1588, 2188189693529)));
+    }
+
+    #[test]
+    fn fuzz_solver() {
+        check_solver(14, 1000, &[EXAMPLE], &[], solve);
+    }
 }
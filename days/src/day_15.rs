@@ -1,5 +1,9 @@
 use self::priority_queue::PriorityQueue;
-use aoc::parse_u32_map;
+use aoc::random::Rng;
+use aoc::read_file;
+use aoc::u32_map_from_str;
+use aoc::validate::check_lines;
+use aoc::validate::Diagnostic;
 use std::collections::HashMap;
 use std::collections::VecDeque;
 
@@ -86,7 +90,7 @@ fn a_star(costs: &HashMap<(i32, i32), u32>, goal: &(i32, i32)) -> VecDeque<(i32,
     panic!("Open set is empty but goal was never reached");
 }
 
-fn solve(input: &HashMap<(i32, i32), u32>) -> usize {
+fn lowest_risk(input: &HashMap<(i32, i32), u32>) -> usize {
     a_star(&input, input.keys().max().unwrap())
         .iter()
         .map(|v| *input.get(v).unwrap() as usize)
@@ -94,21 +98,71 @@ fn solve(input: &HashMap<(i32, i32), u32>) -> usize {
         - *input.get(&(0, 0)).unwrap() as usize
 }
 
-pub fn day_15() -> (usize, usize) {
-    let input = parse_u32_map("day_15".to_string());
-
+/// The full map, made of 5 by 5 tiles of the map with risk levels one higher for every step to the
+/// right or down, wrapping around from 9 to 1.
+fn expand(input: &HashMap<(i32, i32), u32>) -> HashMap<(i32, i32), u32> {
     let mut large_input = HashMap::new();
     let max = input.keys().max().unwrap();
     for i in 0..5 {
         for j in 0..5 {
-            for t in &input {
+            for t in input {
                 large_input.insert(
-                    (t.0.0 + (max.0 + 1) * i, t.0.1 + (max.1 + 1) * j),
+                    (t.0 .0 + (max.0 + 1) * i, t.0 .1 + (max.1 + 1) * j),
                     (t.1 + i as u32 + j as u32 - 1) % 9 + 1,
                 );
             }
         }
     }
+    large_input
+}
+
+/// Check that the risk levels form a rectangular grid of the digits 1 to 9.
+pub fn validate(input: &str) -> Result<(), Vec<Diagnostic>> {
+    check_lines(input, |validator, lines| {
+        validator.grid(lines, 0);
+        for (i, line) in lines.iter().enumerate() {
+            validator.charset(i, line, "123456789");
+        }
+    })
+}
+
+/// Generate a `size` by `size` grid of risk levels.
+pub fn generate(size: usize, seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+    (0..size)
+        .map(|_| {
+            let row = (0..size).map(|_| rng.range(1..=9).to_string());
+            format!("{}\n", row.collect::<String>())
+        })
+        .collect()
+}
+
+/// Parse the risk levels, keyed by row and column.
+pub fn parse(input: &str) -> Result<HashMap<(i32, i32), u32>, Vec<Diagnostic>> {
+    validate(input)?;
+    Ok(u32_map_from_str(input))
+}
+
+/// The lowest total risk of a path through the map, and through the full map.
+pub fn solve(input: &str) -> Result<(usize, usize), Vec<Diagnostic>> {
+    let map = parse(input)?;
+    Ok((lowest_risk(&map), lowest_risk(&expand(&map))))
+}
 
-    (solve(&input), solve(&large_input))
+pub fn day_15() -> (usize, usize) {
+    solve(&read_file("day_15".to_string())).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day_15::solve;
+    use aoc::fuzz::check_solver;
+
+    const EXAMPLE: &str = "1163751742\n1381373672\n2136511328\n3694931569\n7463417111\n\
+         1319128137\n1359912421\n3125421639\n1293138521\n2311944581";
+
+    #[test]
+    fn fuzz_solver() {
+        check_solver(15, 1000, &[EXAMPLE], &[], solve);
+    }
 }
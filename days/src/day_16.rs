@@ -1,10 +1,13 @@
-use aoc::from_bin;
+use aoc::input::Input;
+use aoc::random::Rng;
 use aoc::read_file;
-use std::collections::HashMap;
+use aoc::validate::check_lines;
+use aoc::validate::Diagnostic;
 use std::str::FromStr;
 
+/// A packet of the transmission, with the packets it contains.
 #[derive(Clone, Debug)]
-struct Packet {
+pub struct Packet {
     version: usize,
     type_: usize,
     sub_packets: Vec<Packet>,
@@ -12,62 +15,79 @@ struct Packet {
     remainder: String,
 }
 
+/// Split off the first `n` bits, failing if there are not enough left.
+fn take(input: &str, n: usize) -> Result<(&str, &str), ()> {
+    if input.len() < n {
+        Err(())
+    } else {
+        Ok(input.split_at(n))
+    }
+}
+
+/// Read bits as a number, failing if it does not fit.
+fn number(bits: &str) -> Result<usize, ()> {
+    usize::from_str_radix(bits, 2).map_err(|_| ())
+}
+
 impl FromStr for Packet {
     type Err = ();
 
     fn from_str(input: &str) -> Result<Packet, Self::Err> {
-        let mut split = input.split_at(3);
-        let version = from_bin(&split.0.chars().collect::<Vec<char>>());
-        split = split.1.split_at(3);
-        let type_ = from_bin(&split.0.chars().collect::<Vec<char>>());
+        if !input.chars().all(|c| c == '0' || c == '1') {
+            return Err(());
+        }
+        let mut split = take(input, 3)?;
+        let version = number(split.0)?;
+        split = take(split.1, 3)?;
+        let type_ = number(split.0)?;
         let mut sub_packets = Vec::new();
         let mut literal = 0;
 
-        let mut remainder = String::new();
+        let remainder;
         let ref_remainder;
         if type_ == 4 {
             // Literal.
             let mut continue_ = true;
-            let mut chars = Vec::new();
+            let mut chars = String::new();
             while continue_ {
-                split = split.1.split_at(5);
+                split = take(split.1, 5)?;
                 let bits = split.0.split_at(1);
                 continue_ = bits.0 == "1";
-                chars.extend(bits.1.chars());
+                chars.push_str(bits.1);
             }
-            literal = from_bin(&chars);
+            literal = number(&chars)?;
             remainder = split.1.to_string();
         } else {
             // Operator.
-            split = split.1.split_at(1);
+            split = take(split.1, 1)?;
             let length_type_id = split.0 == "0";
 
             if length_type_id {
-                split = split.1.split_at(15);
-                let package_size_bits = from_bin(&split.0.chars().collect::<Vec<char>>());
-                split = split.1.split_at(package_size_bits);
+                split = take(split.1, 15)?;
+                let package_size_bits = number(split.0)?;
+                split = take(split.1, package_size_bits)?;
                 ref_remainder = split.1;
 
                 let mut done = false;
                 let mut leftover = split.0.to_string();
                 while !done {
-                    let result = Packet::from_str(&leftover).unwrap();
+                    let result = Packet::from_str(&leftover)?;
                     sub_packets.push(result.clone());
                     leftover = result.remainder;
                     done = leftover.len() == 0;
                 }
                 remainder = ref_remainder.to_string();
             } else {
-                split = split.1.split_at(11);
-                let package_size_count = from_bin(&split.0.chars().collect::<Vec<char>>());
+                split = take(split.1, 11)?;
+                let package_size_count = number(split.0)?;
                 let mut leftover = split.1.to_string();
 
                 for _ in 0..package_size_count {
-                    let result = Packet::from_str(&leftover).unwrap();
+                    let result = Packet::from_str(&leftover)?;
                     sub_packets.push(result.clone());
                     leftover = result.remainder.clone();
-                    remainder = result.clone().remainder.clone();
                 }
+                remainder = leftover;
             }
         }
 
@@ -90,78 +110,250 @@ fn recursive_sum(input: &Packet) -> usize {
     sum
 }
 
-fn recursive_eval(input: &Packet) -> usize {
+fn recursive_eval(input: &Packet) -> Option<usize> {
     let subvalues = input
         .sub_packets
         .iter()
-        .map(|p| recursive_eval(p))
-        .collect::<Vec<usize>>();
+        .map(recursive_eval)
+        .collect::<Option<Vec<usize>>>()?;
 
     match input.type_ {
-        0 => subvalues.iter().sum(),
-        1 => subvalues.iter().fold(1, |a, b| a * b),
-        2 => *subvalues.iter().min().unwrap(),
-        3 => *subvalues.iter().max().unwrap(),
-        4 => input.literal,
-        5 => {
-            if subvalues.get(0).unwrap() > subvalues.get(1).unwrap() {
-                1
-            } else {
-                0
-            }
-        }
-        6 => {
-            if subvalues.get(0).unwrap() < subvalues.get(1).unwrap() {
-                1
-            } else {
-                0
+        0 => subvalues.iter().try_fold(0usize, |a, b| a.checked_add(*b)),
+        1 => subvalues.iter().try_fold(1usize, |a, b| a.checked_mul(*b)),
+        2 => subvalues.iter().min().copied(),
+        3 => subvalues.iter().max().copied(),
+        4 => Some(input.literal),
+        5..=7 => match subvalues[..] {
+            [a, b] => Some(match input.type_ {
+                5 => (a > b) as usize,
+                6 => (a < b) as usize,
+                _ => (a == b) as usize,
+            }),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Find an operator with the wrong number of packets inside it: sums, products, minimums and
+/// maximums need at least one, comparisons exactly two.
+fn check_operands(input: &Packet) -> Result<(), String> {
+    let count = input.sub_packets.len();
+    match input.type_ {
+        0..=3 if count == 0 => Err(format!("operator {} has no packets", input.type_)),
+        5..=7 if count != 2 => Err(format!(
+            "comparison {} has {} packets instead of 2",
+            input.type_, count
+        )),
+        _ => input.sub_packets.iter().try_for_each(check_operands),
+    }
+}
+
+impl Packet {
+    /// The sum of the versions of this packet and all packets inside it.
+    pub fn version_sum(&self) -> usize {
+        recursive_sum(self)
+    }
+
+    /// The value of the expression this packet represents, or `None` if it overflows or an
+    /// operator has the wrong number of packets.
+    pub fn value(&self) -> Option<usize> {
+        recursive_eval(self)
+    }
+}
+
+/// Check that the transmission is a single line of hexadecimal digits that forms a packet, with
+/// the right number of packets inside every operator.
+pub fn validate(input: &str) -> Result<(), Vec<Diagnostic>> {
+    check_lines(input, |validator, lines| {
+        if validator.line_count(lines, 1..=1) {
+            validator.check(!lines[0].is_empty(), Some(0), "the transmission is empty");
+            if validator.charset(0, lines[0], "0123456789ABCDEF") {
+                match to_binary(lines[0]).and_then(|b| Packet::from_str(&b)) {
+                    Ok(packet) => {
+                        if let Err(message) = check_operands(&packet) {
+                            validator.error(Some(0), &message);
+                        }
+                    }
+                    Err(_) => {
+                        validator.error(Some(0), "the transmission is not a well formed packet")
+                    }
+                }
             }
         }
-        7 => {
-            if subvalues.get(0).unwrap() == subvalues.get(1).unwrap() {
-                1
-            } else {
-                0
+    })
+}
+
+/// Expand a hexadecimal transmission into its bits.
+fn to_binary(hex: &str) -> Result<String, ()> {
+    hex.chars()
+        .map(|c| c.to_digit(16).map(|d| format!("{:04b}", d)).ok_or(()))
+        .collect()
+}
+
+/// The bits of a random packet that contains `size` packets in total. Products only contain
+/// literals and literals stay below 1000, so that evaluating the packet cannot overflow.
+fn random_packet(rng: &mut Rng, size: usize) -> String {
+    let version = rng.below(8);
+    if size == 1 {
+        let mut value = rng.below(1000);
+        let mut groups = Vec::new();
+        loop {
+            groups.push(value & 0xF);
+            value >>= 4;
+            if value == 0 {
+                break;
             }
         }
-        _ => {
-            panic!("cannot eval")
+        let mut bits = format!("{:03b}100", version);
+        for (i, group) in groups.iter().rev().enumerate() {
+            let last = i + 1 == groups.len();
+            bits.push_str(&format!("{}{:04b}", if last { 0 } else { 1 }, group));
         }
+        return bits;
     }
-}
 
-fn solve(input: &Packet, part_a: bool) -> usize {
-    if part_a {
-        recursive_sum(input)
+    // Comparisons take exactly two packets, the other operators up to four.
+    let remaining = size - 1;
+    let type_id = if remaining <= 3 && rng.chance(0.25) {
+        1
+    } else if remaining >= 2 && rng.chance(0.3) {
+        5 + rng.below(3)
     } else {
-        recursive_eval(input)
+        *rng.choose(&[0, 2, 3])
+    };
+    let count = match type_id {
+        1 => remaining,
+        5..=7 => 2,
+        _ => 1 + rng.below(remaining.min(4)),
+    };
+    let mut sizes = vec![1; count];
+    for _ in count..remaining {
+        sizes[rng.below(count)] += 1;
     }
+    let children = sizes
+        .iter()
+        .map(|s| random_packet(rng, *s))
+        .collect::<String>();
+
+    let length = if children.len() < 1 << 15 && rng.chance(0.5) {
+        format!("0{:015b}", children.len())
+    } else {
+        format!("1{:011b}", count)
+    };
+    format!("{:03b}{:03b}{}{}", version, type_id, length, children)
+}
+
+/// Generate a transmission of `size` packets.
+pub fn generate(size: usize, seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+    // Pad the transmission with zeroes to a whole number of hexadecimal digits.
+    let mut bits = random_packet(&mut rng, size.max(1));
+    bits.push_str(&"0".repeat((4 - bits.len() % 4) % 4));
+    let hex = bits
+        .as_bytes()
+        .chunks(4)
+        .map(|nibble| {
+            let nibble = std::str::from_utf8(nibble).unwrap();
+            format!("{:X}", usize::from_str_radix(nibble, 2).unwrap())
+        })
+        .collect::<String>();
+    format!("{}\n", hex)
+}
+
+/// Parse the outermost packet of the transmission.
+pub fn parse(input: &str) -> Result<Packet, Vec<Diagnostic>> {
+    validate(input)?;
+    let input = Input::new(input);
+    let bits = to_binary(input.lines()[0]).unwrap();
+    Ok(Packet::from_str(&bits).unwrap())
+}
+
+/// The sum of the versions of all packets, and the value of the transmission.
+pub fn solve(input: &str) -> Result<(usize, usize), Vec<Diagnostic>> {
+    let packet = parse(input)?;
+    let value = packet.value().ok_or_else(|| {
+        vec![Diagnostic {
+            line: None,
+            message: "the value of the transmission overflows".to_string(),
+        }]
+    })?;
+    Ok((packet.version_sum(), value))
 }
 
 pub fn day_16() -> (usize, usize) {
-    let mut hb_map = HashMap::new();
-    hb_map.insert('0', "0000".to_string());
-    hb_map.insert('1', "0001".to_string());
-    hb_map.insert('2', "0010".to_string());
-    hb_map.insert('3', "0011".to_string());
-    hb_map.insert('4', "0100".to_string());
-    hb_map.insert('5', "0101".to_string());
-    hb_map.insert('6', "0110".to_string());
-    hb_map.insert('7', "0111".to_string());
-    hb_map.insert('8', "1000".to_string());
-    hb_map.insert('9', "1001".to_string());
-    hb_map.insert('A', "1010".to_string());
-    hb_map.insert('B', "1011".to_string());
-    hb_map.insert('C', "1100".to_string());
-    hb_map.insert('D', "1101".to_string());
-    hb_map.insert('E', "1110".to_string());
-    hb_map.insert('F', "1111".to_string());
-
-    let hex_input = read_file("day_16".to_string());
-    let bin_input = hex_input
-        .chars()
-        .map(|c| hb_map.get(&c).unwrap())
-        .fold(String::new(), |s, a| s + a);
-    let packet = Packet::from_str(&bin_input).unwrap();
-    (solve(&packet, true), solve(&packet, false))
+    solve(&read_file("day_16".to_string())).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day_16::{solve, to_binary, validate, Packet};
+    use aoc::fuzz::check_solver;
+    use aoc::fuzz::Fuzzer;
+    use std::str::FromStr;
+
+    const EXAMPLES: &[&str] = &[
+        "D2FE28",
+        "38006F45291200",
+        "EE00D40C823060",
+        "8A004A801A8002F478",
+        "C0015000016115A2E0802F182340",
+        "9C0141080250320F1802104A08",
+    ];
+
+    // Inputs that once crashed the parser or the solution.
+    const REGRESSIONS: &[&str] = &[
+        "",
+        "0",
+        "D2FE",
+        "38006F452",
+        "G",
+        "0A000",
+        "16004408",
+        "0600848C210842108400246108421084200",
+    ];
+
+    fn parses(input: &str) {
+        let _ = to_binary(input).and_then(|b| Packet::from_str(&b));
+    }
+
+    #[test]
+    fn examples() {
+        let packets = EXAMPLES
+            .iter()
+            .map(|e| Packet::from_str(&to_binary(e).unwrap()).unwrap())
+            .collect::<Vec<Packet>>();
+        assert_eq!(packets[0].literal, 2021);
+        assert_eq!(packets[3].version_sum(), 16);
+        assert_eq!(packets[4].version_sum(), 23);
+        assert_eq!(packets[5].value(), Some(1));
+        assert_eq!(solve(EXAMPLES[4]), Ok((23, 46)));
+    }
+
+    #[test]
+    fn operand_counts_and_overflow() {
+        // A minimum of nothing, and a comparison of a single literal.
+        let empty = validate("0A000").unwrap_err();
+        assert_eq!(empty[0].message, "operator 2 has no packets");
+        let single = validate("16004408").unwrap_err();
+        assert_eq!(single[0].message, "comparison 5 has 1 packets instead of 2");
+
+        // The product of two literals of 2^40.
+        let overflow = solve("0600848C210842108400246108421084200").unwrap_err();
+        assert_eq!(
+            overflow[0].message,
+            "the value of the transmission overflows"
+        );
+    }
+
+    #[test]
+    fn fuzz_parser() {
+        let seeds = [EXAMPLES, REGRESSIONS].concat();
+        Fuzzer::new(16, 2000).check(&seeds, parses);
+    }
+
+    #[test]
+    fn fuzz_solver() {
+        check_solver(16, 2000, EXAMPLES, REGRESSIONS, solve);
+    }
 }
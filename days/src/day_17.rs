@@ -1,12 +1,19 @@
-use aoc::parse_ints;
+use aoc::input::Input;
+use aoc::number_theory::isqrt;
+use aoc::number_theory::triangular;
+use aoc::random::Rng;
+use aoc::read_file;
+use aoc::validate::check_lines;
+use aoc::validate::Diagnostic;
 use std::cmp::max;
 
-#[derive(Clone, Debug)]
-struct Target {
-    x0: isize,
-    x1: isize,
-    y0: isize,
-    y1: isize,
+/// The target area, spanning `x0..=x1` and `y0..=y1`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Target {
+    pub x0: isize,
+    pub x1: isize,
+    pub y0: isize,
+    pub y1: isize,
 }
 
 fn hits_target(x: isize, y: isize, dx: isize, dy: isize, target: &Target) -> (bool, isize) {
@@ -30,27 +37,143 @@ fn hits_target(x: isize, y: isize, dx: isize, dy: isize, target: &Target) -> (bo
     }
 }
 
-fn solve(target: &Target) -> (usize, usize) {
+fn launch(target: &Target) -> (isize, usize) {
+    // Drag stops a probe after travelling `triangular(dx)`, so slower probes never reach the
+    // target. A probe launched upwards at `dy` climbs through `dy` on its first step and comes
+    // back down at `-dy - 1`, so faster probes overshoot the target wherever it lies vertically.
+    let min_dx = (isqrt(2 * target.x0.max(0) as usize).saturating_sub(1)..)
+        .find(|dx| triangular(*dx) as isize >= target.x0)
+        .unwrap() as isize;
+    let max_dy = target.y0.abs().max(target.y1.abs());
     let mut results = Vec::new();
-    for dx in 0..=target.x1 {
-        for dy in target.y0..1000 {
+    for dx in min_dx..=target.x1 {
+        for dy in target.y0.min(0)..=max_dy {
             let result = hits_target(0, 0, dx, dy, target);
             if result.0 {
                 results.push(result.1);
             }
         }
     }
-    (*results.iter().max().unwrap() as usize, results.len())
+    (*results.iter().max().unwrap(), results.len())
+}
+
+/// The farthest the target area may lie in either direction, which keeps the search over initial
+/// velocities short.
+const LIMIT: isize = 1 << 9;
+
+/// The bounds of a target area such as `target area: x=20..30, y=-10..-5`, as written.
+fn bounds(input: &str) -> Option<[&str; 4]> {
+    let (x, y) = input.strip_prefix("target area: x=")?.split_once(", y=")?;
+    let ((x0, x1), (y0, y1)) = (x.split_once("..")?, y.split_once("..")?);
+    Some([x0, x1, y0, y1])
+}
+
+/// Check that the target area lies to the right of and below the launcher.
+pub fn validate(input: &str) -> Result<(), Vec<Diagnostic>> {
+    check_lines(input, |validator, lines| {
+        if !validator.line_count(lines, 1..=1) {
+            return;
+        }
+        let bounds = match bounds(lines[0]) {
+            Some(bounds) => bounds,
+            None => {
+                return validator.error(
+                    Some(0),
+                    "expected a target such as 'target area: x=20..30, y=-10..-5'",
+                )
+            }
+        };
+        let mut v = Vec::new();
+        for bound in &bounds {
+            if let Some(b) = validator.parse::<isize>(0, bound, "bound") {
+                if validator.in_range(0, b, -LIMIT..=LIMIT, "bound") {
+                    v.push(b);
+                }
+            }
+        }
+        if v.len() == 4 {
+            validator.check(
+                v[0] <= v[1] && v[2] <= v[3],
+                Some(0),
+                "the ranges of the target area are reversed",
+            );
+            validator.check(v[0] > 0, Some(0), "the target area must lie to the right");
+            validator.check(
+                v[3] < 0,
+                Some(0),
+                "the target area must lie below the launcher",
+            );
+        }
+    })
+}
+
+/// Generate a target area between `size` and `2 * size` to the right and below the launcher,
+/// where `size` is at most a third of the limit. Like the puzzle inputs, the target holds a point
+/// where some probe stops moving sideways, which the highest shot relies on.
+pub fn generate(size: usize, seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+    let size = size.clamp(1, LIMIT as usize / 3);
+    let stops = (1..)
+        .map(triangular)
+        .skip_while(|t| *t < size)
+        .take_while(|t| *t <= 2 * size)
+        .collect::<Vec<usize>>();
+    let (stop, size) = (*rng.choose(&stops) as isize, size as isize);
+    let x0 = (stop - rng.range(0..=size / 4)).max(1);
+    let x1 = stop + rng.range(0..=size / 4);
+    let y0 = -rng.range(size..=2 * size);
+    let y1 = (y0 + rng.range(0..=size / 2)).min(-1);
+    format!("target area: x={}..{}, y={}..{}\n", x0, x1, y0, y1)
+}
+
+/// Parse the target area.
+pub fn parse(input: &str) -> Result<Target, Vec<Diagnostic>> {
+    validate(input)?;
+    let input = Input::new(input);
+    let v = bounds(input.lines()[0])
+        .and_then(|bounds| {
+            bounds
+                .iter()
+                .map(|b| b.parse().ok())
+                .collect::<Option<Vec<isize>>>()
+        })
+        .ok_or_else(|| {
+            vec![Diagnostic {
+                line: Some(1),
+                message: "the target area is malformed".to_string(),
+            }]
+        })?;
+    Ok(Target {
+        x0: v[0],
+        x1: v[1],
+        y0: v[2],
+        y1: v[3],
+    })
+}
+
+/// The highest position a probe can reach while still hitting the target, and the number of
+/// initial velocities that hit it.
+pub fn solve(input: &str) -> Result<(isize, usize), Vec<Diagnostic>> {
+    Ok(launch(&parse(input)?))
 }
 
 pub fn day_17() -> (usize, usize) {
-    let input = parse_ints("day_17".to_string());
-    let mut iter = input.iter();
-    let target = Target {
-        x0: *iter.next().unwrap(),
-        x1: *iter.next().unwrap(),
-        y0: *iter.next().unwrap(),
-        y1: *iter.next().unwrap(),
-    };
-    solve(&target)
+    let (a, b) = solve(&read_file("day_17".to_string())).unwrap();
+    (a as usize, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day_17::solve;
+    use aoc::fuzz::check_solver;
+
+    const EXAMPLE: &str = "target area: x=20..30, y=-10..-5";
+
+    // Inputs that once hung the solution.
+    const REGRESSIONS: &[&str] = &["target area: x=20..30000000, y=-10..-5"];
+
+    #[test]
+    fn fuzz_solver() {
+        check_solver(17, 1000, &[EXAMPLE], REGRESSIONS, solve);
+    }
 }
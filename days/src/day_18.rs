@@ -1,5 +1,9 @@
+use aoc::input::Input;
 use aoc::ints_from_str;
-use aoc::parse_lines;
+use aoc::random::Rng;
+use aoc::read_file;
+use aoc::validate::check_each_line;
+use aoc::validate::Diagnostic;
 use regex::Regex;
 use std::default::Default;
 use std::iter::Sum;
@@ -22,8 +26,9 @@ fn is_balanced(s: String) -> bool {
     counter == 0
 }
 
+/// A snailfish number: either a regular number or a pair of snailfish numbers.
 #[derive(Clone, Debug)]
-struct SnailNumber {
+pub struct SnailNumber {
     nest: Option<(Box<SnailNumber>, Box<SnailNumber>)>,
     literal: Option<usize>,
 }
@@ -84,34 +89,28 @@ impl FromStr for SnailNumber {
                 ..Default::default()
             }),
             Err(_) => {
+                if input.len() < 2 || !input.starts_with('[') || !input.ends_with(']') {
+                    return Err(());
+                }
+                // Split on the first comma that is not nested any deeper.
                 let s = &input[1..input.len() - 1];
-                for i in 0..s.len() {
-                    let m = &s[i..i + 1].chars().next().unwrap();
-                    if *m == ',' {
+                for (i, m) in s.char_indices() {
+                    if m == ',' {
                         let s1 = &s[0..i];
                         let s2 = &s[i + 1..s.len()];
 
                         if is_balanced(s1.to_string()) && s1.len() > 0 && s2.len() > 0 {
                             return Ok(SnailNumber {
                                 nest: Option::Some((
-                                    Box::new(SnailNumber::from_str(s1).unwrap()),
-                                    Box::new(SnailNumber::from_str(s2).unwrap()),
+                                    Box::new(SnailNumber::from_str(s1)?),
+                                    Box::new(SnailNumber::from_str(s2)?),
                                 )),
                                 ..Default::default()
                             });
                         }
                     }
                 }
-
-                // Last resort to resolve syntax error.
-                if input.len() > 2 {
-                    let mut stripped_input = input.to_string();
-                    stripped_input = stripped_input.replace("[[", "[");
-                    stripped_input = stripped_input.replace("]]", "]");
-                    return SnailNumber::from_str(&stripped_input);
-                } else {
-                    panic!("invalid snail number '{}'", input);
-                }
+                Err(())
             }
         }
     }
@@ -234,7 +233,30 @@ fn split(str: &String) -> String {
 }
 
 impl SnailNumber {
-    fn magnitude(&self) -> usize {
+    /// A random regular number or pair, nested at most `depth` pairs deep.
+    fn random(rng: &mut Rng, depth: usize) -> SnailNumber {
+        if depth == 0 || rng.chance(0.3) {
+            SnailNumber {
+                literal: Some(rng.below(10)),
+                ..Default::default()
+            }
+        } else {
+            SnailNumber::random_pair(rng, depth)
+        }
+    }
+
+    /// A random pair, nested at most `depth` pairs deep.
+    fn random_pair(rng: &mut Rng, depth: usize) -> SnailNumber {
+        let left = SnailNumber::random(rng, depth - 1);
+        let right = SnailNumber::random(rng, depth - 1);
+        SnailNumber {
+            nest: Some((Box::new(left), Box::new(right))),
+            ..Default::default()
+        }
+    }
+
+    /// Three times the magnitude of the left element plus twice the magnitude of the right.
+    pub fn magnitude(&self) -> usize {
         match self.literal {
             Some(n) => n,
             None => {
@@ -245,7 +267,7 @@ impl SnailNumber {
     }
 }
 
-fn solve(input: &Vec<SnailNumber>, part_a: bool) -> usize {
+fn do_homework(input: &Vec<SnailNumber>, part_a: bool) -> usize {
     if part_a {
         input
             .iter()
@@ -266,20 +288,62 @@ fn solve(input: &Vec<SnailNumber>, part_a: bool) -> usize {
     }
 }
 
-pub fn day_18() -> (usize, usize) {
-    let input = parse_lines("day_18".to_string());
-    let numbers = input
+/// Check that every line is a well formed pair of snail numbers, whose regular numbers are single
+/// digits as in a reduced number. Larger ones would take ever longer to split.
+pub fn validate(input: &str) -> Result<(), Vec<Diagnostic>> {
+    check_each_line(input, |validator, i, line| {
+        if validator.charset(i, line, "[],0123456789") {
+            validator.check(
+                line.starts_with('[') && SnailNumber::from_str(line).is_ok(),
+                Some(i),
+                "not a well formed snail number pair",
+            );
+            validator.check(
+                line.split(|c| "[],".contains(c)).all(|n| n.len() <= 1),
+                Some(i),
+                "regular numbers must be single digits",
+            );
+        }
+    })
+}
+
+/// Generate `size` snail numbers, nested no deeper than the puzzle inputs.
+pub fn generate(size: usize, seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+    (0..size)
+        .map(|_| format!("{}\n", SnailNumber::random_pair(&mut rng, 4)))
+        .collect()
+}
+
+/// Parse the snailfish numbers of the homework.
+pub fn parse(input: &str) -> Result<Vec<SnailNumber>, Vec<Diagnostic>> {
+    validate(input)?;
+    let input = Input::new(input);
+    Ok(input
+        .lines()
         .iter()
-        .map(|s| SnailNumber::from_str(s).unwrap())
-        .collect::<Vec<SnailNumber>>();
+        .map(|l| SnailNumber::from_str(l).unwrap())
+        .collect())
+}
 
-    (solve(&numbers, true), solve(&numbers, false))
+/// The magnitude of the sum of all numbers, and the largest magnitude of the sum of two numbers.
+pub fn solve(input: &str) -> Result<(usize, usize), Vec<Diagnostic>> {
+    let numbers = parse(input)?;
+    Ok((do_homework(&numbers, true), do_homework(&numbers, false)))
+}
+
+pub fn day_18() -> (usize, usize) {
+    solve(&read_file("day_18".to_string())).unwrap()
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day_18::SnailNumber;
-    use crate::day_18::{is_balanced, solve};
+    use crate::day_18::{do_homework as solve, is_balanced};
+    use aoc::fuzz::check_solver;
+    use aoc::fuzz::Fuzzer;
+    use aoc::input::Input;
+    use aoc::random::Rng;
     use std::str::FromStr;
 
     #[test]
@@ -351,4 +415,49 @@ mod tests {
         let result = format!("{}", input.iter().map(|s| s.clone()).sum::<SnailNumber>());
         assert_eq!(result, "[[[[5,0],[7,4]],[5,5]],[6,6]]");
     }
+
+    const EXAMPLE: &str =
+        "[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]\n[[[5,[2,8]],4],[5,[[9,9],0]]]";
+
+    // Inputs that once crashed the parser, "[1]" recursed until the stack overflowed, or hung the
+    // solution.
+    const REGRESSIONS: &[&str] = &["[1]", "[]", "1\n\n2", "[é,1]", "[999999999,1]"];
+
+    fn round_trips(input: &str) {
+        for line in Input::new(input).lines() {
+            if let Ok(number) = SnailNumber::from_str(line) {
+                assert_eq!(SnailNumber::from_str(&number.to_string()), Ok(number));
+            }
+        }
+    }
+
+    #[test]
+    fn fuzz_parser() {
+        let seeds = [&[EXAMPLE], REGRESSIONS].concat();
+        Fuzzer::new(18, 2000).check(&seeds, round_trips);
+    }
+
+    #[test]
+    fn fuzz_solver() {
+        check_solver(18, 1000, &[EXAMPLE], REGRESSIONS, crate::day_18::solve);
+    }
+
+    fn random_number(rng: &mut Rng, depth: usize) -> String {
+        if depth == 0 || rng.chance(0.3) {
+            rng.below(10).to_string()
+        } else {
+            let left = random_number(rng, depth - 1);
+            format!("[{},{}]", left, random_number(rng, depth - 1))
+        }
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let mut rng = Rng::new(18);
+        for _ in 0..500 {
+            let left = random_number(&mut rng, 4);
+            let text = format!("[{},{}]", left, random_number(&mut rng, 4));
+            assert_eq!(SnailNumber::from_str(&text).unwrap().to_string(), text);
+        }
+    }
 }
@@ -1,4 +1,10 @@
-use aoc::parse_items;
+use aoc::input::Input;
+use aoc::random::Rng;
+use aoc::read_file;
+use aoc::rotation::Rotation;
+use aoc::validate::check_lines;
+use aoc::validate::sections;
+use aoc::validate::Diagnostic;
 use std::collections::{HashSet, VecDeque};
 use std::convert::TryInto;
 use std::str::FromStr;
@@ -14,24 +20,23 @@ impl FromStr for Coordinate {
     type Err = ();
 
     fn from_str(input: &str) -> Result<Coordinate, Self::Err> {
-        let mut split = input.split(",");
-        let coordinate = Coordinate {
-            x: split.next().unwrap_or("a").parse().unwrap_or(isize::MAX),
-            y: split.next().unwrap_or("a").parse().unwrap_or(isize::MAX),
-            z: split.next().unwrap_or("0").parse().unwrap_or(isize::MAX),
-        };
-        if coordinate.x == isize::MAX || coordinate.y == isize::MAX || coordinate.z == isize::MAX {
-            Err(())
-        } else {
-            Ok(coordinate)
+        let values = input
+            .split(',')
+            .map(|v| v.parse())
+            .collect::<Result<Vec<isize>, _>>()
+            .map_err(|_| ())?;
+        match values[..] {
+            [x, y, z] => Ok(Coordinate { x, y, z }),
+            _ => Err(()),
         }
     }
 }
 
+/// A scanner and the beacons it detects, relative to its own position and orientation.
 #[derive(Clone, Debug)]
-struct Sensor {
+pub struct Sensor {
     location: Coordinate,
-    rotation: u8, // 3D space has 24 rotations
+    rotation: Rotation,
     results: HashSet<Coordinate>,
 }
 
@@ -40,16 +45,13 @@ impl FromStr for Sensor {
 
     fn from_str(input: &str) -> Result<Sensor, Self::Err> {
         let location = Coordinate { x: 0, y: 0, z: 0 };
-        let rotation = 0;
-        let lines = input.split("\n");
-        let mut results: HashSet<Coordinate> = HashSet::new();
-
-        for line in lines {
-            let result = Coordinate::from_str(line);
-            if result.is_ok() {
-                results.insert(result.unwrap());
-            }
-        }
+        let rotation = Rotation::identity();
+        // Skip the header, every other line must be a coordinate.
+        let results = input
+            .split('\n')
+            .skip_while(|l| l.starts_with("---"))
+            .map(Coordinate::from_str)
+            .collect::<Result<HashSet<Coordinate>, ()>>()?;
 
         return Ok(Sensor {
             location,
@@ -61,141 +63,10 @@ impl FromStr for Sensor {
 
 impl Sensor {
     fn rotate(&self, c: &mut Coordinate) {
-        // Z is the face, so we only rotate X and Y.
-        let x = c.x;
-        let y = c.y;
-        let z = c.z;
-        match self.rotation {
-            // Centre.
-            0 => {
-                c.x = x;
-                c.y = y;
-                c.z = z;
-            }
-            1 => {
-                c.x = -y;
-                c.y = x;
-                c.z = z;
-            }
-            2 => {
-                c.x = y;
-                c.y = -x;
-                c.z = z;
-            }
-            3 => {
-                c.x = -x;
-                c.y = -y;
-                c.z = z;
-            }
-            // Left.
-            4 => {
-                c.x = x;
-                c.z = -y;
-                c.y = z;
-            }
-            5 => {
-                c.x = -y;
-                c.z = -x;
-                c.y = z;
-            }
-            6 => {
-                c.x = y;
-                c.z = x;
-                c.y = z;
-            }
-            7 => {
-                c.x = -x;
-                c.z = y;
-                c.y = z;
-            }
-            // Right.
-            8 => {
-                c.x = x;
-                c.z = y;
-                c.y = -z;
-            }
-            9 => {
-                c.x = -y;
-                c.z = x;
-                c.y = -z;
-            }
-            10 => {
-                c.x = y;
-                c.z = -x;
-                c.y = -z;
-            }
-            11 => {
-                c.x = -x;
-                c.z = -y;
-                c.y = -z;
-            }
-            // 180.
-            12 => {
-                c.x = x;
-                c.y = -y;
-                c.z = -z;
-            }
-            13 => {
-                c.x = -y;
-                c.y = -x;
-                c.z = -z;
-            }
-            14 => {
-                c.x = y;
-                c.y = x;
-                c.z = -z;
-            }
-            15 => {
-                c.x = -x;
-                c.y = y;
-                c.z = -z;
-            }
-            // Up.
-            16 => {
-                c.z = x;
-                c.y = y;
-                c.x = -z;
-            }
-            17 => {
-                c.z = -y;
-                c.y = x;
-                c.x = -z;
-            }
-            18 => {
-                c.z = y;
-                c.y = -x;
-                c.x = -z;
-            }
-            19 => {
-                c.z = -x;
-                c.y = -y;
-                c.x = -z;
-            }
-            // Down.
-            20 => {
-                c.z = -x;
-                c.y = y;
-                c.x = z;
-            }
-            21 => {
-                c.z = y;
-                c.y = x;
-                c.x = z;
-            }
-            22 => {
-                c.z = -y;
-                c.y = -x;
-                c.x = z;
-            }
-            23 => {
-                c.z = x;
-                c.y = -y;
-                c.x = z;
-            }
-            _ => {
-                panic!("unknown rotation {}", self.rotation);
-            }
-        };
+        let [x, y, z] = self.rotation.apply([c.x, c.y, c.z]);
+        c.x = x;
+        c.y = y;
+        c.z = z;
     }
 
     fn shift(&self, c: &mut Coordinate) {
@@ -235,11 +106,10 @@ impl Sensor {
     }
 }
 
-fn solve(input: &Vec<Sensor>) -> (usize, usize) {
-    let mut queue: VecDeque<Sensor> = input
-        .iter()
-        .map(|r| r.clone())
-        .collect::<VecDeque<Sensor>>();
+/// Place every scanner next to one already placed, or `None` if some of them never overlap with
+/// the others.
+fn assemble(input: &[Sensor]) -> Option<(usize, usize)> {
+    let mut queue = input.iter().cloned().collect::<VecDeque<Sensor>>();
     let mut found_set = HashSet::new();
 
     // Pop the first, this is our starting point.
@@ -249,13 +119,15 @@ fn solve(input: &Vec<Sensor>) -> (usize, usize) {
     let mut sensors: Vec<Coordinate> = Vec::new();
     sensors.push(first.location);
 
-    // Now try to match the next with a circular buffer, once the buffer is empty we are done.
+    // Now try to match the next with a circular buffer, once the buffer is empty we are done. If
+    // every scanner in the buffer misses in a row, nothing is left to match them against.
+    let mut misses = 0;
     while !queue.is_empty() {
         let mut next = queue.pop_front().unwrap();
 
         // Check if for any configuration we have `n` overlap.
         let mut found = false;
-        for rotation in 0..24 {
+        for rotation in Rotation::all() {
             // If we found a match, break.
             if found {
                 break;
@@ -285,8 +157,14 @@ fn solve(input: &Vec<Sensor>) -> (usize, usize) {
         }
 
         // If no match was found, add to the back of the circular buffer.
-        if !found {
+        if found {
+            misses = 0;
+        } else {
             queue.push_back(next);
+            misses += 1;
+            if misses == queue.len() {
+                return None;
+            }
         }
     }
 
@@ -303,14 +181,161 @@ fn solve(input: &Vec<Sensor>) -> (usize, usize) {
 
     let furthest_distance: usize = (*distances.iter().max().unwrap()).try_into().unwrap();
 
-    (found_set.len(), furthest_distance)
+    Some((found_set.len(), furthest_distance))
+}
+
+/// The largest coordinate either way, which keeps the arithmetic on coordinates well within an
+/// isize.
+const LIMIT: isize = 1 << 20;
+
+/// Check that every scanner has a header followed by beacon coordinates.
+pub fn validate(input: &str) -> Result<(), Vec<Diagnostic>> {
+    check_lines(input, |validator, lines| {
+        let sections = sections(lines);
+        validator.non_empty(lines);
+        for (start, scanner) in &sections {
+            validator.check(
+                scanner[0].starts_with("--- scanner ") && scanner[0].ends_with(" ---"),
+                Some(*start),
+                "expected a header such as '--- scanner 0 ---'",
+            );
+            validator.check(
+                scanner.len() > 1,
+                Some(*start),
+                "the scanner has no beacons",
+            );
+            for (i, beacon) in scanner.iter().enumerate().skip(1) {
+                match Coordinate::from_str(beacon) {
+                    Ok(c) => {
+                        for v in &[c.x, c.y, c.z] {
+                            validator.in_range(start + i, *v, -LIMIT..=LIMIT, "coordinate");
+                        }
+                    }
+                    Err(_) => validator.error(
+                        Some(start + i),
+                        &format!("'{}' is not a coordinate such as '-1,2,3'", beacon),
+                    ),
+                }
+            }
+        }
+    })
+}
+
+/// Generate `size` scanners along a random walk. Every scanner sees 13 beacons of its own and the
+/// 13 beacons of the next scanner, so neighbouring scanners always overlap by at least 12.
+pub fn generate(size: usize, seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+    let rotations = Rotation::all();
+    let mut center = [0, 0, 0];
+    let mut beacons = Vec::new();
+    let mut scanners = Vec::new();
+    for _ in 0..=size {
+        for _ in 0..13 {
+            beacons.push(center.map(|c| c + rng.range(-500..=500)));
+        }
+        scanners.push(center.map(|c| c + rng.range(-100..=100)));
+        center = center.map(|c| c + rng.range(-800..=800));
+    }
+
+    let mut output = String::new();
+    for (i, location) in scanners[..size].iter().enumerate() {
+        // A scanner reports beacons relative to itself, in its own orientation.
+        let inverse = rng.choose(&rotations).inverse();
+        let mut seen = beacons[13 * i..13 * (i + 2)]
+            .iter()
+            .map(|b| inverse.apply([0, 1, 2].map(|k| b[k] - location[k])))
+            .collect::<Vec<[isize; 3]>>();
+        rng.shuffle(&mut seen);
+        if i > 0 {
+            output.push('\n');
+        }
+        output.push_str(&format!("--- scanner {} ---\n", i));
+        for [x, y, z] in seen {
+            output.push_str(&format!("{},{},{}\n", x, y, z));
+        }
+    }
+    output
+}
+
+/// Parse the reports of the scanners.
+pub fn parse(input: &str) -> Result<Vec<Sensor>, Vec<Diagnostic>> {
+    validate(input)?;
+    Ok(Input::new(input).parse_sections::<Sensor>().unwrap())
+}
+
+/// The number of beacons, and the largest Manhattan distance between two scanners.
+pub fn solve(input: &str) -> Result<(usize, usize), Vec<Diagnostic>> {
+    assemble(&parse(input)?).ok_or_else(|| {
+        vec![Diagnostic {
+            line: None,
+            message: "some scanners do not overlap with the others".to_string(),
+        }]
+    })
 }
 
 pub fn day_19() -> (usize, usize) {
-    let input = parse_items("day_19".to_string(), "\n\n".to_string());
-    let sensors = input
-        .iter()
-        .map(|s| Sensor::from_str(s).unwrap())
-        .collect::<Vec<Sensor>>();
-    solve(&sensors)
+    solve(&read_file("day_19".to_string())).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day_19::{assemble, generate, solve, validate, Coordinate, Sensor};
+    use aoc::fuzz::check_parser;
+    use aoc::fuzz::check_solver;
+    use aoc::input::Input;
+    use aoc::random::Rng;
+    use aoc::rotation::Rotation;
+    use std::collections::HashSet;
+
+    fn observe(beacons: &[[isize; 3]], location: [isize; 3], rotation: &Rotation) -> Sensor {
+        // A sensor reports beacons relative to itself, in its own orientation.
+        let inverse = rotation.inverse();
+        let results = beacons
+            .iter()
+            .map(|b| inverse.apply([b[0] - location[0], b[1] - location[1], b[2] - location[2]]))
+            .map(|[x, y, z]| Coordinate { x, y, z })
+            .collect::<HashSet<Coordinate>>();
+        Sensor {
+            location: Coordinate { x: 0, y: 0, z: 0 },
+            rotation: Rotation::identity(),
+            results,
+        }
+    }
+
+    #[test]
+    fn rotated_sensors() {
+        let mut rng = Rng::new(19);
+        let mut next = || rng.range(-500..=499);
+        let beacons = (0..40)
+            .map(|_| [next(), next(), next()])
+            .collect::<Vec<[isize; 3]>>();
+        let rotations = Rotation::all();
+        let sensors = vec![
+            observe(&beacons[0..20], [0, 0, 0], &Rotation::identity()),
+            observe(&beacons[5..30], [100, -50, 20], &rotations[7]),
+            observe(&beacons[15..40], [-30, 60, 5], &rotations[19]),
+        ];
+        assert_eq!(assemble(&sensors), Some((40, 255)));
+    }
+
+    const EXAMPLE: &str =
+        "--- scanner 0 ---\n404,-588,-901\n528,-643,409\n\n--- scanner 1 ---\n686,422,578";
+
+    // Inputs that once crashed the parser or hung the solution.
+    const REGRESSIONS: &[&str] = &[
+        "--- scanner 0 ---\n1,2,3\n\n--- scanner 1 ---\n4,5,6",
+        "--- scanner 0 ---\n9223372036854775807,0,0\n\n--- scanner 1 ---\n-9223372036854775807,0,0",
+    ];
+
+    #[test]
+    fn fuzz_parser() {
+        let parses = |input: &str| Input::new(input).parse_sections::<Sensor>().is_ok();
+        check_parser(19, 2000, &[EXAMPLE], REGRESSIONS, validate, parses);
+    }
+
+    #[test]
+    fn fuzz_solver() {
+        let generated = generate(3, 19);
+        check_solver(19, 200, &[EXAMPLE, &generated], REGRESSIONS, solve);
+    }
 }
@@ -1,107 +1,166 @@
-use aoc::from_bin;
-use aoc::parse_items;
-use std::collections::HashSet;
-
-fn get_neighborhood(input: &(isize, isize)) -> Vec<(isize, isize)> {
-    let mut neighborhood = Vec::new();
-    for i in -1..=1 {
-        for j in -1..=1 {
-            // Ordering is important!
-            neighborhood.push((input.0 + i, input.1 + j));
-        }
-    }
-    neighborhood
+use aoc::automaton::Automaton;
+use aoc::automaton::Boundary;
+use aoc::automaton::Neighbourhood;
+use aoc::input::Input;
+use aoc::random::Rng;
+use aoc::read_file;
+use aoc::render::invalid_input;
+use aoc::render::FrameWriter;
+use aoc::validate::check_lines;
+use aoc::validate::sections;
+use aoc::validate::Diagnostic;
+use std::io;
+use std::path::Path;
+
+fn read_image(input: &Input) -> (Vec<bool>, Automaton<bool>) {
+    // Parse the input, the algorithm may be wrapped over several lines.
+    let sections = input.sections();
+    let algorithm = sections[0]
+        .lines()
+        .concat()
+        .chars()
+        .map(|c| c == '#')
+        .collect::<Vec<bool>>();
+    let rows = sections[1]
+        .lines()
+        .iter()
+        .map(|l| l.chars().map(|c| c == '#').collect())
+        .collect();
+
+    // Ordering is important! The neighbourhood is read as a binary number, row by row.
+    let neighbourhood = Neighbourhood::Offsets(
+        (-1..=1)
+            .flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+            .collect(),
+    );
+    let image = Automaton::new(rows, neighbourhood, Boundary::Infinite(false));
+    (algorithm, image)
 }
 
-fn will_be_lit(
-    input: &(isize, isize),
-    lit: &HashSet<(isize, isize)>,
-    unlit: &HashSet<(isize, isize)>,
-    algorithm: &Vec<char>,
-    default_on: &bool,
-) -> bool {
-    let mut bin = Vec::new();
-    for neighbor in get_neighborhood(input) {
-        if *default_on {
-            if unlit.contains(&neighbor) {
-                bin.push('0');
-            } else {
-                bin.push('1');
-            }
-        } else {
-            if lit.contains(&neighbor) {
-                bin.push('1');
-            } else {
-                bin.push('0');
-            }
+fn enhance(algorithm: &[bool], neighbours: &[bool]) -> bool {
+    algorithm[neighbours.iter().fold(0, |i, b| i * 2 + *b as usize)]
+}
+
+fn lit_pixels(algorithm: &[bool], mut image: Automaton<bool>) -> (usize, usize) {
+    let mut part_a = 0;
+    for i in 0..50 {
+        if i == 2 {
+            part_a = image.count(|c| *c);
         }
+        image.step(|_, n| enhance(algorithm, n));
     }
+    (part_a, image.count(|c| *c))
+}
 
-    let index = from_bin(&bin);
-    return algorithm.get(index).unwrap() == &'#';
+/// Write the image after every enhancement as bitmaps.
+pub fn render(input: &str, directory: &Path) -> io::Result<()> {
+    let (algorithm, mut image) = parse(input).map_err(invalid_input)?;
+    let mut frames = FrameWriter::new(directory, "day_20")?;
+
+    frames.write_pbm(&image.rows())?;
+    for _ in 0..50 {
+        image.step(|_, n| enhance(&algorithm, n));
+        frames.write_pbm(&image.rows())?;
+    }
+    Ok(())
 }
 
-fn solve(input: &Vec<String>) -> (usize, usize) {
-    // Parse the input into mutable hash sets.
-    let algorithm = input.get(0).unwrap().chars().collect::<Vec<char>>();
-    let lines = input
-        .get(1)
-        .unwrap()
-        .split("\n")
-        .map(|s| s.to_string())
-        .collect::<Vec<String>>();
-    let mut lit: HashSet<(isize, isize)> = HashSet::new();
-    let mut unlit: HashSet<(isize, isize)> = HashSet::new();
-    for i in 0..lines.len() {
-        let line = lines.get(i).unwrap();
-        for j in 0..(line.len() + 2) {
-            let c = line.chars().nth(j).unwrap_or('.');
-            match c {
-                '#' => {
-                    lit.insert((i as isize, j as isize));
-                }
-                '.' => {
-                    unlit.insert((i as isize, j as isize));
-                }
-                _ => {
-                    panic!("non-binary pixel {}", c)
-                }
-            };
+/// Check that a 512 character algorithm, which may wrap over several lines, is followed by an
+/// image of light and dark pixels.
+pub fn validate(input: &str) -> Result<(), Vec<Diagnostic>> {
+    check_lines(input, |validator, lines| {
+        let sections = sections(lines);
+        if !validator.check(
+            sections.len() == 2,
+            None,
+            &format!(
+                "expected an algorithm and an image separated by a blank line, found {} sections",
+                sections.len()
+            ),
+        ) {
+            return;
         }
-    }
 
-    let mut part_a = 0;
-    for i in 0..50 {
-        if i == 2 {
-            part_a = lit.len();
+        let (start, algorithm) = &sections[0];
+        let length = algorithm.iter().map(|l| l.chars().count()).sum::<usize>();
+        validator.check(
+            length == 512,
+            Some(*start),
+            &format!("expected an algorithm of 512 characters, found {}", length),
+        );
+        for (i, line) in algorithm.iter().enumerate() {
+            validator.charset(start + i, line, "#.");
+        }
+        let (start, image) = &sections[1];
+        validator.grid(image, *start);
+        for (i, line) in image.iter().enumerate() {
+            validator.charset(start + i, line, "#.");
         }
+    })
+}
 
-        let default_on = (i % 2 == 1) && algorithm.get(0).unwrap() == &'#';
-        // Get all candidates to be lit.
-        let candidates = lit
-            .union(&unlit)
-            .map(|c| get_neighborhood(c))
-            .flatten()
-            .collect::<HashSet<(isize, isize)>>();
-
-        // Evaluate candidates against neighbors and the algorithm.
-        let filtered_candidates = candidates
-            .iter()
-            .filter(|c| will_be_lit(c, &lit, &unlit, &algorithm, &default_on))
-            .map(|c| *c)
-            .collect::<HashSet<(isize, isize)>>();
-
-        // Replace.
-        lit = filtered_candidates;
-        unlit = candidates
-            .difference(&lit)
-            .map(|c| *c)
-            .collect::<HashSet<(isize, isize)>>();
+/// Generate a random algorithm and a `size` by `size` image. If the algorithm lights up dark
+/// areas, it also turns lit areas dark, so that the number of lit pixels stays finite.
+pub fn generate(size: usize, seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+    let mut algorithm = (0..512)
+        .map(|_| if rng.chance(0.5) { '#' } else { '.' })
+        .collect::<Vec<char>>();
+    if algorithm[0] == '#' {
+        algorithm[511] = '.';
+    }
+    let mut output = format!("{}\n\n", algorithm.into_iter().collect::<String>());
+    for _ in 0..size {
+        let row = (0..size).map(|_| if rng.chance(0.5) { '#' } else { '.' });
+        output.push_str(&format!("{}\n", row.collect::<String>()));
     }
-    return (part_a, lit.len());
+    output
+}
+
+/// Parse the image enhancement algorithm, and the image as an automaton on an infinite dark
+/// background.
+pub fn parse(input: &str) -> Result<(Vec<bool>, Automaton<bool>), Vec<Diagnostic>> {
+    validate(input)?;
+    Ok(read_image(&Input::new(input)))
+}
+
+/// The number of lit pixels after enhancing the image twice, and after enhancing it 50 times.
+pub fn solve(input: &str) -> Result<(usize, usize), Vec<Diagnostic>> {
+    let (algorithm, image) = parse(input)?;
+    Ok(lit_pixels(&algorithm, image))
 }
 
 pub fn day_20() -> (usize, usize) {
-    let input = parse_items("day_20".to_string(), "\n\n".to_string());
-    solve(&input)
+    solve(&read_file("day_20".to_string())).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day_20::solve;
+    use aoc::fuzz::check_solver;
+
+    const EXAMPLE: &str =
+        "..#.#..#####.#.#.#.###.##.....###.##.#..###.####..#####..#....#..#..##..##\n\
+         #..######.###...####..#..#####..##..#.#####...##.#.#..#.##..#.#......#.###\n\
+         .######.###.####...#.##.##..#..#..#####.....#.#....###..#.##......#.....#.\n\
+         .#..#..##..#...##.######.####.####.#.#...#.......#..#.#.#...####.##.#.....\n\
+         .#..#...##.#.##..#...##.#.##..###.#......#.#.......#.#.#.####.###.##...#..\n\
+         ...####.#..#..#.##.#....##..#.####....##...##..#...#......#.#.......#.....\n\
+         ..##..####..#...#.#.#...##..#.#..###..#####........#..####......#..#\n\
+         \n\
+         #..#.\n\
+         #....\n\
+         ##..#\n\
+         ..#..\n\
+         ..###";
+
+    #[test]
+    fn example() {
+        assert_eq!(solve(EXAMPLE), Ok((35, 3351)));
+    }
+
+    #[test]
+    fn fuzz_solver() {
+        check_solver(20, 500, &[EXAMPLE], &[], solve);
+    }
 }
@@ -1,6 +1,10 @@
-use aoc::parse_ints;
+use aoc::input::Input;
+use aoc::memo::Memo;
+use aoc::random::Rng;
+use aoc::read_file;
+use aoc::validate::check_lines;
+use aoc::validate::Diagnostic;
 use std::cmp::max;
-use std::collections::HashMap;
 
 struct DeterministicDie {
     next_value: usize,
@@ -26,9 +30,15 @@ impl Default for DeterministicDie {
     }
 }
 
-fn solve(input: &Vec<isize>, part_a: bool) -> usize {
-    let p1 = input.get(1).unwrap().clone() as usize;
-    let p2 = input.get(3).unwrap().clone() as usize;
+/// Read the starting position of a player from a line such as `Player 1 starting position: 4`.
+fn starting_position(line: &str, player: usize) -> Option<usize> {
+    line.strip_prefix(&format!("Player {} starting position: ", player))?
+        .parse()
+        .ok()
+        .filter(|p| (1..=10).contains(p))
+}
+
+fn play((p1, p2): (usize, usize), part_a: bool) -> usize {
     if part_a {
         let mut position_one = p1;
         let mut position_two = p2;
@@ -51,62 +61,114 @@ fn solve(input: &Vec<isize>, part_a: bool) -> usize {
         }
         panic!("the die broke")
     } else {
-        let mut state_count: HashMap<(usize, usize, usize, usize), usize> = HashMap::new();
-        state_count.insert((p1, p2, 0, 0), 1);
-
-        let mut p1_win = 0;
-        let mut p2_win = 0;
-
-        for i in 0..1000 {
-            // Evolve.
-            let mut new_map: HashMap<(usize, usize, usize, usize), usize> = HashMap::new();
-            for d1 in 1..=3 {
-                for d2 in 1..=3 {
-                    for d3 in 1..=3 {
-                        for (key, value) in &state_count {
-                            let new_key;
-                            if i % 2 == 0 {
-                                let position_one = (key.0 - 1 + d1 + d2 + d3) % 10 + 1;
-                                let score_one = key.2 + position_one;
-
-                                new_key = (position_one, key.1, score_one, key.3);
+        // Count the universes in which the player to move and the other player win from a state of
+        // (position to move, other position, score to move, other score).
+        let mut wins = Memo::new(
+            |recurse, state: (usize, usize, usize, usize)| -> (usize, usize) {
+                let (position, other_position, score, other_score) = state;
+                let mut wins = (0, 0);
+                for d1 in 1..=3 {
+                    for d2 in 1..=3 {
+                        for d3 in 1..=3 {
+                            let new_position = (position - 1 + d1 + d2 + d3) % 10 + 1;
+                            let new_score = score + new_position;
+                            if new_score >= 21 {
+                                wins.0 += 1;
                             } else {
-                                let position_two = (key.1 - 1 + d1 + d2 + d3) % 10 + 1;
-                                let score_two = key.3 + position_two;
-                                new_key = (key.0, position_two, key.2, score_two);
-                            }
-
-                            if new_map.contains_key(&new_key) {
-                                let mut_value = new_map.get_mut(&new_key).unwrap();
-                                *mut_value += value;
-                            } else {
-                                new_map.insert(new_key, value.clone());
+                                // The other player moves next, so the result is mirrored.
+                                let result =
+                                    recurse((other_position, new_position, other_score, new_score));
+                                wins.0 += result.1;
+                                wins.1 += result.0;
                             }
                         }
                     }
                 }
-            }
+                wins
+            },
+        );
+        let (p1_win, p2_win) = wins.get((p1, p2, 0, 0));
+        max(p1_win, p2_win)
+    }
+}
 
-            // Count wins.
-            let mut new_new_map: HashMap<(usize, usize, usize, usize), usize> = HashMap::new();
-            for (key, value) in new_map {
-                if key.2 >= 21 {
-                    p1_win += value;
-                } else if key.3 >= 21 {
-                    p2_win += value;
-                } else {
-                    new_new_map.insert(key, value);
-                }
+/// Check that both players have a starting position on the board.
+pub fn validate(input: &str) -> Result<(), Vec<Diagnostic>> {
+    check_lines(input, |validator, lines| {
+        if validator.line_count(lines, 2..=2) {
+            for (i, line) in lines.iter().enumerate() {
+                validator.check(
+                    starting_position(line, i + 1).is_some(),
+                    Some(i),
+                    &format!(
+                        "expected 'Player {} starting position: ' followed by 1 to 10",
+                        i + 1
+                    ),
+                );
             }
-
-            // Finish iteration.
-            state_count = new_new_map;
         }
-        max(p1_win, p2_win)
-    }
+    })
+}
+
+/// Generate two random starting positions. `size` is ignored, the game always has two players on
+/// a track of 10 spaces.
+pub fn generate(_size: usize, seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+    format!(
+        "Player 1 starting position: {}\nPlayer 2 starting position: {}\n",
+        rng.range(1..=10),
+        rng.range(1..=10)
+    )
+}
+
+/// Parse the starting positions of both players.
+pub fn parse(input: &str) -> Result<(usize, usize), Vec<Diagnostic>> {
+    validate(input)?;
+    let input = Input::new(input);
+    let lines = input.lines();
+    Ok((
+        starting_position(lines[0], 1).unwrap(),
+        starting_position(lines[1], 2).unwrap(),
+    ))
+}
+
+/// The score of the losing player times the number of rolls with the deterministic die, and the
+/// number of universes in which the player who wins most often wins with the Dirac die.
+pub fn solve(input: &str) -> Result<(usize, usize), Vec<Diagnostic>> {
+    let positions = parse(input)?;
+    Ok((play(positions, true), play(positions, false)))
 }
 
 pub fn day_21() -> (usize, usize) {
-    let input = parse_ints("day_21".to_string());
-    (solve(&input, true), solve(&input, false))
+    solve(&read_file("day_21".to_string())).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day_21::{solve, validate};
+    use aoc::fuzz::check_solver;
+
+    const EXAMPLE: &str = "Player 1 starting position: 4\nPlayer 2 starting position: 8";
+
+    #[test]
+    fn example() {
+        assert_eq!(solve(EXAMPLE), Ok((739785, 444356092776315)));
+    }
+
+    #[test]
+    fn rejects_positions_off_the_board() {
+        let errors = validate("Player 1 starting position: 0\nPlayer 2 starting position: 11");
+        let lines = errors
+            .unwrap_err()
+            .iter()
+            .map(|e| e.line)
+            .collect::<Vec<_>>();
+        assert_eq!(lines, vec![Some(1), Some(2)]);
+        assert!(validate("Player 1 starting position: 1\nPlayer 2 starting position: 10").is_ok());
+    }
+
+    #[test]
+    fn fuzz_solver() {
+        check_solver(21, 500, &[EXAMPLE], &[], solve);
+    }
 }
@@ -1,19 +1,23 @@
-// Disclaimer: this one got messy, I decided to go for a range but that came back to
-// haunt me as it does not implement the Copy trait, resulting in excessive cloning.
-use aoc::ints_from_str;
-use aoc::parse_lines;
-use std::cmp::max;
-use std::cmp::min;
-use std::collections::HashSet;
-use std::ops::RangeInclusive;
+use aoc::input::Input;
+use aoc::interval::Interval;
+use aoc::interval::Region;
+use aoc::random::Rng;
+use aoc::read_file;
+use aoc::validate::check_each_line;
+use aoc::validate::Diagnostic;
+use lazy_static::lazy_static;
+use regex::Regex;
 use std::str::FromStr;
 
-#[derive(Debug, Eq, PartialEq, Hash)]
-struct Cuboid {
-    on: bool,
-    xs: RangeInclusive<isize>,
-    ys: RangeInclusive<isize>,
-    zs: RangeInclusive<isize>,
+lazy_static! {
+    static ref INT: Regex = Regex::new(r"[-+]?\d+").unwrap();
+}
+
+/// A reboot step, turning the cubes in a region on or off.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Cuboid {
+    pub on: bool,
+    pub region: Region<3>,
 }
 
 impl FromStr for Cuboid {
@@ -21,162 +25,134 @@ impl FromStr for Cuboid {
 
     fn from_str(input: &str) -> Result<Cuboid, Self::Err> {
         let on = input.contains("on");
-        let v = ints_from_str(&input.to_string());
-        let mut is = v.iter();
-        let xs = is.next().unwrap().to_owned()..=is.next().unwrap().to_owned();
-        let ys = is.next().unwrap().to_owned()..=is.next().unwrap().to_owned();
-        let zs = is.next().unwrap().to_owned()..=is.next().unwrap().to_owned();
-        return Ok(Cuboid { on, xs, ys, zs });
-    }
-}
-
-impl Clone for Cuboid {
-    fn clone(&self) -> Cuboid {
-        Cuboid {
-            on: self.on,
-            xs: *self.xs.start()..=*self.xs.end(),
-            ys: *self.ys.start()..=*self.ys.end(),
-            zs: *self.zs.start()..=*self.zs.end(),
+        let v = INT
+            .find_iter(input)
+            .map(|m| m.as_str().parse().map_err(|_| ()))
+            .collect::<Result<Vec<isize>, ()>>()?;
+        if v.len() != 6 {
+            return Err(());
         }
+        let axis = |i: usize| Interval::checked_inclusive(v[i], v[i + 1]).ok_or(());
+        let region = Region::new([axis(0)?, axis(2)?, axis(4)?]);
+        Ok(Cuboid { on, region })
     }
 }
 
-impl Cuboid {
-    fn volume(self) -> usize {
-        ((self.xs.end() - self.xs.start() + 1)
-            * (self.ys.end() - self.ys.start() + 1)
-            * (self.zs.end() - self.zs.start() + 1)) as usize
-    }
+fn reboot(steps: &[Cuboid]) -> (usize, usize) {
+    // Keep a set of disjoint lit regions, every new cuboid carves itself out of the existing ones.
+    let mut lit: Vec<Region<3>> = Vec::new();
 
-    fn limited_volume(self) -> usize {
-        let cuboid = Cuboid {
-            on: self.on,
-            xs: max(-50, *self.xs.start())..=min(50, *self.xs.end()),
-            ys: max(-50, *self.ys.start())..=min(50, *self.ys.end()),
-            zs: max(-50, *self.zs.start())..=min(50, *self.zs.end()),
-        };
-        if cuboid.clone().is_valid() {
-            return cuboid.volume();
-        } else {
-            return 0;
+    for cuboid in steps {
+        lit = lit
+            .iter()
+            .flat_map(|b| b.difference(&cuboid.region))
+            .collect();
+        if cuboid.on {
+            lit.push(cuboid.region);
         }
     }
 
-    fn is_valid(self) -> bool {
-        (*self.xs.start() <= *self.xs.end())
-            && (*self.ys.start() <= *self.ys.end())
-            && (*self.zs.start() <= *self.zs.end())
-    }
-
-    fn intersection(self, other: &Cuboid) -> Cuboid {
-        return Cuboid {
-            on: self.on,
-            xs: max(*self.xs.start(), *other.xs.start())..=min(*self.xs.end(), *other.xs.end()),
-            ys: max(*self.ys.start(), *other.ys.start())..=min(*self.ys.end(), *other.ys.end()),
-            zs: max(*self.zs.start(), *other.zs.start())..=min(*self.zs.end(), *other.zs.end()),
-        };
-    }
-
-    // Get up to 7 cuboids.
-    fn get_many_cuboids(self, inner: &Cuboid) -> Vec<Cuboid> {
-        let mut output: Vec<Cuboid> = Vec::new();
-
-        // Left plate.
-        output.push(Cuboid {
-            on: self.on,
-            xs: *self.xs.start()..=(*inner.xs.start() - 1),
-            ys: *self.ys.start()..=*self.ys.end(),
-            zs: *self.zs.start()..=*self.zs.end(),
-        });
-        // Right plate.
-        output.push(Cuboid {
-            on: self.on,
-            xs: (*inner.xs.end() + 1)..=*self.xs.end(),
-            ys: *self.ys.start()..=*self.ys.end(),
-            zs: *self.zs.start()..=*self.zs.end(),
-        });
-        // Front pillar.
-        output.push(Cuboid {
-            on: self.on,
-            xs: *inner.xs.start()..=*inner.xs.end(),
-            ys: *self.ys.start()..=(*inner.ys.start() - 1),
-            zs: *self.zs.start()..=*self.zs.end(),
-        });
-        // Back pillar
-        output.push(Cuboid {
-            on: self.on,
-            xs: *inner.xs.start()..=*inner.xs.end(),
-            ys: (inner.ys.end() + 1)..=*self.ys.end(),
-            zs: *self.zs.start()..=*self.zs.end(),
-        });
-        // Bottom slab.
-        output.push(Cuboid {
-            on: self.on,
-            xs: *inner.xs.start()..=*inner.xs.end(),
-            ys: *inner.ys.start()..=*inner.ys.end(),
-            zs: *self.zs.start()..=(*inner.zs.start() - 1),
-        });
-        // Upper slab.
-        output.push(Cuboid {
-            on: self.on,
-            xs: *inner.xs.start()..=*inner.xs.end(),
-            ys: *inner.ys.start()..=*inner.ys.end(),
-            zs: (*inner.zs.end() + 1)..=*self.zs.end(),
-        });
-
-        output
-            .into_iter()
-            .filter(|c| c.clone().is_valid())
-            .collect()
-    }
-
-    // Shatter the other.
-    fn shatter(self, other: &Cuboid) -> Vec<Cuboid> {
-        // Find the joint cuboid.
-        let joint_cuboid = self.intersection(other);
+    let initialization_region = Region::new([Interval::inclusive(-50, 50); 3]);
+    (
+        lit.iter()
+            .map(|b| b.intersection(&initialization_region).volume())
+            .sum(),
+        lit.iter().map(|b| b.volume()).sum(),
+    )
+}
 
-        if !joint_cuboid.clone().is_valid() {
-            return vec![other.clone()];
+const LIMIT: isize = 1 << 20;
+
+/// Check that every line turns a cuboid such as `on x=-20..26,y=-36..17,z=-47..7` on or off.
+pub fn validate(input: &str) -> Result<(), Vec<Diagnostic>> {
+    check_each_line(input, |validator, i, line| {
+        let ranges = line
+            .strip_prefix("on ")
+            .or_else(|| line.strip_prefix("off "))
+            .unwrap_or("");
+        if !validator.check(
+            ranges.starts_with("x=") && ranges.contains(",y=") && ranges.contains(",z="),
+            Some(i),
+            "expected a step such as 'on x=10..12,y=10..12,z=10..12'",
+        ) {
+            return;
         }
+        if let Some(v) = validator.ints(i, ranges, 6) {
+            validator.check(
+                v[0] <= v[1] && v[2] <= v[3] && v[4] <= v[5],
+                Some(i),
+                "the cuboid has a reversed range",
+            );
+            // Keep volumes within a usize.
+            for value in v {
+                validator.in_range(i, value, -LIMIT..=LIMIT, "coordinate");
+            }
+        }
+    })
+}
 
-        other.clone().get_many_cuboids(&joint_cuboid)
+/// Generate `size` reboot steps. Like the puzzle inputs the first 20 steps stay within the
+/// initialization area, and the first step turns cubes on.
+pub fn generate(size: usize, seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+    let mut output = String::new();
+    for i in 0..size {
+        let state = if i == 0 || rng.chance(0.5) {
+            "on"
+        } else {
+            "off"
+        };
+        let ranges = (0..3)
+            .map(|_| {
+                let (start, end) = if i < 20 {
+                    let start = rng.range(-50..=40);
+                    (start, start + rng.range(0..=10))
+                } else {
+                    let start = rng.range(-100_000..=70_000);
+                    (start, start + rng.range(1_000..=30_000))
+                };
+                format!("{}..{}", start, end)
+            })
+            .collect::<Vec<String>>();
+        output.push_str(&format!(
+            "{} x={},y={},z={}\n",
+            state, ranges[0], ranges[1], ranges[2]
+        ));
     }
+    output
 }
 
-fn solve(input: &Vec<String>) -> (usize, usize) {
-    let mut cuboid_set: HashSet<Cuboid> = HashSet::new();
-
-    for new_cuboid in input.iter().map(|s| Cuboid::from_str(s).unwrap()) {
-        let mut new_cuboids = HashSet::new();
-        for cuboid in cuboid_set {
-            for shard in new_cuboid.clone().shatter(&cuboid) {
-                new_cuboids.insert(shard);
-            }
-        }
-        new_cuboids.insert(new_cuboid);
-
-        let active_cuboids = new_cuboids
-            .iter()
-            .filter(|c| c.on)
-            .map(|c| c.clone())
-            .collect();
+/// Parse the reboot steps.
+pub fn parse(input: &str) -> Result<Vec<Cuboid>, Vec<Diagnostic>> {
+    validate(input)?;
+    let input = Input::new(input);
+    Ok(input
+        .lines()
+        .iter()
+        .map(|l| Cuboid::from_str(l).unwrap())
+        .collect())
+}
 
-        cuboid_set = active_cuboids;
-    }
-    (
-        cuboid_set.iter().map(|c| c.clone().limited_volume()).sum(),
-        cuboid_set.iter().map(|c| c.clone().volume()).sum(),
-    )
+/// The number of cubes that are on within the initialization region, and in total.
+pub fn solve(input: &str) -> Result<(usize, usize), Vec<Diagnostic>> {
+    Ok(reboot(&parse(input)?))
 }
 
 pub fn day_22() -> (usize, usize) {
-    let input = parse_lines("day_22".to_string());
-    solve(&input)
+    solve(&read_file("day_22".to_string())).unwrap()
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::day_22::solve;
+    use crate::day_22::{validate, Cuboid};
+    use aoc::fuzz::check_parser;
+    use aoc::fuzz::check_solver;
+    use aoc::input::Input;
+    use std::str::FromStr;
+
+    fn solve(input: &[String]) -> (usize, usize) {
+        crate::day_22::solve(&input.join("\n")).unwrap()
+    }
 
     #[test]
     fn simplest_case() {
@@ -217,4 +193,28 @@ mod tests {
         ];
         assert_eq!(solve(&input).0, 590784);
     }
+
+    const EXAMPLE: &str = "on x=-20..26,y=-36..17,z=-47..7\noff x=-48..-32,y=26..41,z=-47..-37";
+
+    // Inputs that once crashed the parser.
+    const REGRESSIONS: &[&str] = &[
+        "on x=99999999999999999999..1,y=1..1,z=1..1",
+        "on x=0..9223372036854775807,y=0..0,z=0..0",
+    ];
+
+    #[test]
+    fn fuzz_parser() {
+        let parses = |input: &str| {
+            Input::new(input)
+                .lines()
+                .iter()
+                .all(|l| Cuboid::from_str(l).is_ok())
+        };
+        check_parser(22, 2000, &[EXAMPLE], REGRESSIONS, validate, parses);
+    }
+
+    #[test]
+    fn fuzz_solver() {
+        check_solver(22, 1000, &[EXAMPLE], REGRESSIONS, crate::day_22::solve);
+    }
 }
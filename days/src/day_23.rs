@@ -1,7 +1,10 @@
+use aoc::random::Rng;
+use aoc::read_file;
+use aoc::validate::check_lines;
+use aoc::validate::Diagnostic;
 extern crate lazy_static;
 
 use aoc::manhattan_distance;
-use aoc::parse_chars;
 use lazy_static::lazy_static;
 use std::cmp::Ordering;
 use std::collections::BinaryHeap;
@@ -140,8 +143,9 @@ impl Amphipod {
     }
 }
 
+/// A burrow with amphipods, and the energy spent to get there.
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
-struct State {
+pub struct State {
     // Easy way to make a max-heap a min-heap: we go with negative scores.
     cost: isize,
     // A state consists of 8-16 amphipodes.
@@ -289,7 +293,7 @@ impl State {
     }
 }
 
-fn solve(input: State) -> usize {
+fn organize(input: State) -> usize {
     let mut heap: BinaryHeap<State> = BinaryHeap::new();
     heap.push(input);
     let part_a;
@@ -315,11 +319,11 @@ fn solve(input: State) -> usize {
     part_a
 }
 
-fn parse_input(input: Vec<char>) -> State {
+fn parse_input(input: &str) -> State {
     let mut amphipodes = Vec::new();
 
     let mut i = 0;
-    for c in input {
+    for c in input.chars() {
         let cost_per_move = match c {
             'A' => 1,
             'B' => 10,
@@ -351,19 +355,105 @@ fn parse_input(input: Vec<char>) -> State {
     }
 }
 
-pub fn day_23() -> (usize, usize) {
-    // TODO: Remove second input file.
-    let input_a = parse_chars("day_23_a".to_string());
-    let input_b = parse_chars("day_23_b".to_string());
+/// The rows of a burrow without their indentation, where a `?` is a place in a room. Folded
+/// burrows have one row like the fourth, unfolded ones three.
+const BURROW: [&str; 5] = [
+    "#############",
+    "#...........#",
+    "###?#?#?#?###",
+    "#?#?#?#?#",
+    "#########",
+];
+
+/// Check that the burrow has an empty hallway above rooms of two or four places, and that it
+/// holds two or four amphipods of every type and nothing else.
+pub fn validate(input: &str) -> Result<(), Vec<Diagnostic>> {
+    check_lines(input, |validator, lines| {
+        if !validator.check(
+            lines.len() == 5 || lines.len() == 7,
+            None,
+            &format!("expected 5 or 7 lines, found {}", lines.len()),
+        ) {
+            return;
+        }
+        let rows = BURROW[..3]
+            .iter()
+            .chain(vec![&BURROW[3]; lines.len() - 4])
+            .chain(&BURROW[4..]);
+        for (i, (line, row)) in lines.iter().zip(rows).enumerate() {
+            let matches = line.len() == row.len()
+                && line.chars().zip(row.chars()).all(|(c, r)| match r {
+                    '?' => "ABCD".contains(c),
+                    _ => c == r,
+                });
+            validator.check(
+                matches,
+                Some(i),
+                &format!("expected a row such as '{}'", row.replace('?', "A")),
+            );
+        }
+        let counts = ['A', 'B', 'C', 'D']
+            .iter()
+            .map(|a| lines.iter().map(|l| l.matches(*a).count()).sum())
+            .collect::<Vec<usize>>();
+        validator.check(
+            counts.iter().all(|c| *c == counts[0]),
+            None,
+            &format!(
+                "expected as many amphipods of every type, found {:?} of A, B, C and D",
+                counts
+            ),
+        );
+    })
+}
+
+/// Generate a burrow with the amphipods in a random order. `size` is ignored, the folded burrow
+/// always holds two amphipods of each type. The unfolded burrow for the second part follows from
+/// it with `unfold`.
+pub fn generate(_size: usize, seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+    let mut amphipods = "AABBCCDD".chars().collect::<Vec<char>>();
+    rng.shuffle(&mut amphipods);
+    let a = &amphipods;
+    format!(
+        "#############\n#...........#\n###{}#{}#{}#{}###\n  #{}#{}#{}#{}#\n  #########\n",
+        a[0], a[1], a[2], a[3], a[4], a[5], a[6], a[7]
+    )
+}
+
+/// Insert the two rows the second part adds to a burrow.
+pub fn unfold(input: &str) -> String {
+    let mut lines = input.lines().collect::<Vec<&str>>();
+    lines.splice(3..3, ["  #D#C#B#A#", "  #D#B#A#C#"]);
+    format!("{}\n", lines.join("\n"))
+}
+
+/// Parse the burrow, folded or unfolded.
+pub fn parse(input: &str) -> Result<State, Vec<Diagnostic>> {
+    validate(input)?;
+    Ok(parse_input(input))
+}
+
+/// The least energy needed to organize the amphipods in the burrow, the second part is solved on
+/// the `unfold`ed burrow.
+pub fn solve(input: &str) -> Result<usize, Vec<Diagnostic>> {
+    Ok(organize(parse(input)?))
+}
 
-    (solve(parse_input(input_a)), solve(parse_input(input_b)))
+pub fn day_23() -> (usize, usize) {
+    (
+        solve(&read_file("day_23_a".to_string())).unwrap(),
+        solve(&read_file("day_23_b".to_string())).unwrap(),
+    )
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::day_23::solve;
+    use crate::day_23::organize as solve;
+    use crate::day_23::validate;
     use crate::day_23::Amphipod;
     use crate::day_23::State;
+    use aoc::fuzz::check_solver;
 
     #[test]
     fn simple_case() {
@@ -522,4 +612,29 @@ mod tests {
         };
         assert_eq!(solve(state), 0);
     }
+
+    // Organizing a random burrow takes longer than the fuzzer waits for, so the burrows it starts
+    // from are nearly organized.
+    const NEARLY_ORGANIZED: &[&str] = &[
+        "#############\n#...........#\n###A#B#C#D###\n  #A#B#C#D#\n  #########",
+        "#############\n#...........#\n###B#A#C#D###\n  #A#B#C#D#\n  #########",
+    ];
+
+    #[test]
+    fn rejects_misplaced_amphipods() {
+        assert!(validate(NEARLY_ORGANIZED[1]).is_ok());
+        let errors =
+            validate("#############\n#...........#\n###A#A#B#C#D#\n  #######B#C#D#\n  #########")
+                .unwrap_err();
+        assert_eq!(errors[0].line, Some(3));
+    }
+
+    // Inputs that once hung the solution.
+    const REGRESSIONS: &[&str] =
+        &["#############\n#...........#\n###A#A#B#C#D#\n  #######B#C#D#\n  #########"];
+
+    #[test]
+    fn fuzz_solver() {
+        check_solver(23, 200, NEARLY_ORGANIZED, REGRESSIONS, crate::day_23::solve);
+    }
 }
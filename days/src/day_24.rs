@@ -1,8 +1,14 @@
+use aoc::input::Input;
+use aoc::memo::Memo;
+use aoc::random::Rng;
 use aoc::read_file;
+use aoc::validate::check_lines;
+use aoc::validate::Diagnostic;
 use std::str::FromStr;
 
+/// One of the 14 blocks of the program, each reading a single digit.
 #[derive(Debug)]
-struct SubProgram {
+pub struct SubProgram {
     a: isize,
     b: isize,
     truncate: bool,
@@ -36,6 +42,11 @@ impl SubProgram {
     }
 }
 
+/// The constant at the end of an instruction such as `add x 12`.
+fn constant(line: &str) -> Result<isize, ()> {
+    line.split(' ').nth(2).ok_or(())?.parse().map_err(|_| ())
+}
+
 impl FromStr for SubProgram {
     type Err = ();
 
@@ -45,121 +56,281 @@ impl FromStr for SubProgram {
             return Err(());
         }
 
-        let a: isize = lines
-            .get(5)
-            .unwrap()
-            .split(" ")
-            .collect::<Vec<&str>>()
-            .get(2)
-            .unwrap()
-            .parse()
-            .unwrap();
-        let b: isize = lines
-            .get(15)
-            .unwrap()
-            .split(" ")
-            .collect::<Vec<&str>>()
-            .get(2)
-            .unwrap()
-            .parse()
-            .unwrap();
-        let truncate: bool = lines
-            .get(4)
-            .unwrap()
-            .split(" ")
-            .collect::<Vec<&str>>()
-            .get(2)
-            .unwrap()
-            .parse::<isize>()
-            .unwrap()
-            == 26;
+        let a = constant(lines[5])?;
+        let b = constant(lines[15])?;
+        let truncate = constant(lines[4])? == 26;
 
         return Ok(SubProgram { a, b, truncate });
     }
 }
 
-fn run_program(program: &Vec<SubProgram>, inputs: &Vec<isize>) -> isize {
-    let mut output = 0;
-    for i in 0..14 {
-        output = program.get(i).unwrap().run(inputs.get(i).unwrap(), &output);
+/// The largest or smallest model number the program accepts, or `None` if it accepts none.
+fn model_number(program: &[SubProgram], maximize: bool) -> Option<usize> {
+    // Only truncating subprograms make z smaller, each by a factor of 26 at most, so z has to stay
+    // below 26 to the power of the number of them left to be able to reach 0.
+    let mut bounds = vec![1; program.len() + 1];
+    for (index, subprogram) in program.iter().enumerate().rev() {
+        let factor = if subprogram.truncate { 26 } else { 1 };
+        bounds[index] = bounds[index + 1] * factor;
     }
-    output
-}
 
-fn solve_recursive(
-    maximize: bool,
-    program: &Vec<SubProgram>,
-    inputs: &mut Vec<isize>,
-    z: isize,
-) -> Result<Vec<isize>, &'static str> {
-    if (inputs.len() == 14) && run_program(program, inputs) == 0 {
-        return Ok(inputs.clone());
-    } else if inputs.len() == 14 {
-        return Err("End result did not match");
-    }
+    // Find the best digits for the remaining subprograms given the index of the next one and the
+    // current value of z. Dead ends are cached, so each state is only explored once.
+    let mut best_digits = Memo::new(
+        |recurse, (index, z): (usize, isize)| -> Option<Vec<isize>> {
+            if z >= bounds[index] {
+                return None;
+            }
+            if index == program.len() {
+                return Some(Vec::new());
+            }
 
-    // If truncate is true and we cannot reduce, error out.
-    let current_index: usize = inputs.len();
-    let subprogram = program.get(current_index).unwrap();
-    if subprogram.truncate {
-        let best_choice_option = if maximize {
-            subprogram.highest_option(&z)
-        } else {
-            subprogram.lowest_option(&z)
-        };
-        match best_choice_option {
-            Some(best_choice) => {
-                inputs.push(best_choice);
-                match solve_recursive(maximize, program, inputs, subprogram.run(&best_choice, &z)) {
-                    Ok(result) => return Ok(result),
-                    Err(_) => inputs.pop(),
+            // If truncate is true, try the digit that reduces first and error out if none does.
+            let subprogram = program.get(index).unwrap();
+            let mut is = Vec::new();
+            if subprogram.truncate {
+                let best_choice_option = if maximize {
+                    subprogram.highest_option(&z)
+                } else {
+                    subprogram.lowest_option(&z)
+                };
+                match best_choice_option {
+                    Some(best_choice) => is.push(best_choice),
+                    _ => return None,
                 };
             }
-            _ => return Err("No match for truncate step"),
-        };
-    }
 
-    // Very simple strategy, just go over range 9..=1 and hope the search space is
-    // small enough with only 7 subprograms to brute force.
-    let is = if maximize {
-        vec![9, 8, 7, 6, 5, 4, 3, 2, 1]
-    } else {
-        vec![1, 2, 3, 4, 5, 6, 7, 8, 9]
-    };
-    for i in is {
-        // Push on vector.
-        inputs.push(i);
+            // Very simple strategy, just go over range 9..=1 and hope the search space is
+            // small enough with only 7 subprograms to brute force.
+            if maximize {
+                is.extend((1..=9).rev());
+            } else {
+                is.extend(1..=9);
+            }
+            for i in is {
+                if let Some(mut digits) = recurse((index + 1, subprogram.run(&i, &z))) {
+                    digits.insert(0, i);
+                    return Some(digits);
+                }
+            }
+            None
+        },
+    );
+
+    best_digits.get((0, 0)).map(|digits| {
+        digits
+            .iter()
+            .map(|i| format!("{}", i))
+            .collect::<String>()
+            .parse::<usize>()
+            .unwrap()
+    })
+}
 
-        let result = solve_recursive(maximize, program, inputs, subprogram.run(&i, &z));
+/// The largest offset a block may add to x or y, which keeps z within an isize.
+const LIMIT: isize = 1 << 16;
 
-        match result {
-            Ok(result) => return Ok(result),
-            Err(_) => inputs.pop(),
-        };
-    }
-    Err("Ran out of options...")
+/// Check that the program consists of valid instructions, and has the 14 blocks of 18
+/// instructions the solver reads its constants from. The offsets added to y may not be negative,
+/// so that z never is either.
+pub fn validate(input: &str) -> Result<(), Vec<Diagnostic>> {
+    check_lines(input, |validator, lines| {
+        let registers = ["w", "x", "y", "z"];
+        for (i, line) in lines.iter().enumerate() {
+            let parts = line.split(' ').collect::<Vec<&str>>();
+            let operands = match parts[0] {
+                "inp" => 1,
+                "add" | "mul" | "div" | "mod" | "eql" => 2,
+                instruction => {
+                    validator.error(Some(i), &format!("unknown instruction '{}'", instruction));
+                    continue;
+                }
+            };
+            if !validator.check(
+                parts.len() == operands + 1,
+                Some(i),
+                &format!("'{}' takes {} operands", parts[0], operands),
+            ) {
+                continue;
+            }
+            validator.check(
+                registers.contains(&parts[1]),
+                Some(i),
+                &format!("'{}' is not a register", parts[1]),
+            );
+            if operands == 2 && !registers.contains(&parts[2]) {
+                validator.parse::<isize>(i, parts[2], "register or number");
+            }
+        }
+        if !validator.is_valid() || !validator.line_count(lines, 252..=252) {
+            return;
+        }
+
+        for start in (0..lines.len()).step_by(18) {
+            validator.check(lines[start] == "inp w", Some(start), "expected 'inp w'");
+            validator.check(
+                lines[start + 4] == "div z 1" || lines[start + 4] == "div z 26",
+                Some(start + 4),
+                "expected 'div z 1' or 'div z 26'",
+            );
+            for (offset, register, lowest) in [(5, "x", -LIMIT), (15, "y", 0)] {
+                let i = start + offset;
+                let prefix = format!("add {} ", register);
+                match lines[i].strip_prefix(&prefix) {
+                    Some(number) => {
+                        if let Some(n) = validator.parse::<isize>(i, number, "offset") {
+                            validator.in_range(i, n, lowest..=LIMIT, "offset");
+                        }
+                    }
+                    None => validator.error(
+                        Some(i),
+                        &format!("expected 'add {}' followed by a number", register),
+                    ),
+                }
+            }
+        }
+    })
 }
 
-fn solve(program: &Vec<SubProgram>, maximize: bool) -> usize {
-    let mut inputs = Vec::new();
-    let result = solve_recursive(maximize, program, &mut inputs, 0);
-    result
-        .unwrap()
+fn decompile(input: &str) -> Vec<SubProgram> {
+    Input::new(input)
+        .as_str()
+        .split("inp w")
+        .filter_map(|i| SubProgram::from_str(i).ok())
+        .collect()
+}
+
+/// The program for the given (divisor, x offset, y offset) of every digit.
+fn program(digits: &[(isize, isize, isize)]) -> String {
+    digits
         .iter()
-        .map(|i| format!("{}", i))
-        .collect::<String>()
-        .parse::<usize>()
-        .unwrap()
+        .map(|(d, a, b)| {
+            format!(
+                "inp w\nmul x 0\nadd x z\nmod x 26\ndiv z {}\nadd x {}\neql x w\neql x 0\n\
+                 mul y 0\nadd y 25\nmul y x\nadd y 1\nmul z y\nmul y 0\nadd y w\nadd y {}\n\
+                 mul y x\nadd z y\n",
+                d, a, b
+            )
+        })
+        .collect()
+}
+
+/// Generate a program with the usual structure: 7 digits push onto a stack in base 26 and 7 pop
+/// from it, in a random order. The offsets are picked so that every popping digit can match the
+/// digit it pops, which makes sure that a valid model number exists. `size` is ignored, model
+/// numbers always have 14 digits.
+pub fn generate(_size: usize, seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+    let mut digits = Vec::new();
+    let mut stack = Vec::new();
+    let mut pushes = 7;
+    for _ in 0..14 {
+        if pushes > 0 && (stack.is_empty() || rng.chance(0.5)) {
+            // An x offset of 10 or more never matches a digit, so the digit is always pushed.
+            let b = rng.range(0..=16);
+            stack.push(b);
+            pushes -= 1;
+            digits.push((1, rng.range(10..=16), b));
+        } else {
+            // The popping digit equals the pushed digit plus `b + a`, which must stay within 8.
+            let b = stack.pop().unwrap();
+            digits.push((26, rng.range(-8 - b..=8 - b), rng.range(0..=16)));
+        }
+    }
+    program(&digits)
+}
+
+/// Parse the program into the blocks for every digit.
+pub fn parse(input: &str) -> Result<Vec<SubProgram>, Vec<Diagnostic>> {
+    validate(input)?;
+    Ok(decompile(input))
+}
+
+/// The largest and the smallest model number the program accepts.
+pub fn solve(input: &str) -> Result<(usize, usize), Vec<Diagnostic>> {
+    let subprograms = parse(input)?;
+    let model_number = |maximize| {
+        model_number(&subprograms, maximize).ok_or_else(|| {
+            vec![Diagnostic {
+                line: None,
+                message: "the program accepts no model number".to_string(),
+            }]
+        })
+    };
+    Ok((model_number(true)?, model_number(false)?))
 }
 
 pub fn day_24() -> (usize, usize) {
-    let input = read_file("day_24".to_string());
+    solve(&read_file("day_24".to_string())).unwrap()
+}
 
-    let subprograms: Vec<SubProgram> = input
-        .split("inp w")
-        .map(|i| SubProgram::from_str(i))
-        .filter(|o| o.is_ok())
-        .map(|s| s.unwrap())
-        .collect::<Vec<SubProgram>>();
-    (solve(&subprograms, true), solve(&subprograms, false))
+#[cfg(test)]
+mod tests {
+    use crate::day_24::{decompile, generate, program, solve, validate};
+    use aoc::fuzz::check_parser;
+    use aoc::fuzz::check_solver;
+
+    // The (divisor, x offset, y offset) of every digit, in the usual MONAD layout.
+    const DIGITS: [(isize, isize, isize); 14] = [
+        (1, 11, 7),
+        (1, 14, 8),
+        (1, 10, 16),
+        (1, 14, 8),
+        (26, -8, 3),
+        (1, 14, 12),
+        (26, -11, 1),
+        (1, 10, 8),
+        (26, -6, 8),
+        (26, -9, 14),
+        (1, 12, 4),
+        (26, -5, 14),
+        (26, -4, 15),
+        (26, -9, 6),
+    ];
+
+    // Inputs that once crashed the parser.
+    const REGRESSIONS: &[&str] = &["inp w\n\n\n\n\n\n\n\n\n\n\n\n\n\n\n"];
+
+    #[test]
+    fn fuzz_parser() {
+        let program = program(&DIGITS);
+        assert!(validate(&program).is_ok());
+        let parses = |input: &str| decompile(input).len() == 14;
+        check_parser(24, 500, &[&program], REGRESSIONS, validate, parses);
+    }
+
+    #[test]
+    fn rejects_large_offsets() {
+        let mut digits = DIGITS;
+        digits[0].2 = isize::MAX;
+        assert_eq!(validate(&program(&digits)).unwrap_err()[0].line, Some(16));
+        let mut digits = DIGITS;
+        digits[4].1 = isize::MIN;
+        assert!(validate(&program(&digits)).is_err());
+        let mut digits = DIGITS;
+        digits[2].2 = -1;
+        assert!(validate(&program(&digits)).is_err());
+    }
+
+    #[test]
+    fn rejects_programs_without_model_numbers() {
+        // The fifth digit pops the fourth, which would have to be 17 higher.
+        let mut digits = DIGITS;
+        digits[4].1 = 9;
+        assert_eq!(
+            solve(&program(&digits)).unwrap_err()[0].message,
+            "the program accepts no model number"
+        );
+    }
+
+    #[test]
+    fn fuzz_solver() {
+        check_solver(
+            24,
+            500,
+            &[&program(&DIGITS), &generate(0, 24)],
+            REGRESSIONS,
+            solve,
+        );
+    }
 }
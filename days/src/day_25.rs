@@ -1,119 +1,171 @@
-use aoc::parse_lines;
+use aoc::automaton::Automaton;
+use aoc::automaton::Boundary;
+use aoc::automaton::Neighbourhood;
+use aoc::input::Input;
+use aoc::random::Rng;
+use aoc::read_file;
+use aoc::render::invalid_input;
+use aoc::render::FrameWriter;
+use aoc::validate::check_lines;
+use aoc::validate::Diagnostic;
 use std::collections::HashSet;
+use std::io;
+use std::path::Path;
 
-#[derive(PartialEq, Eq)]
-struct State {
-    east: HashSet<(isize, isize)>,
-    south: HashSet<(isize, isize)>,
-    max_x: isize,
-    max_y: isize,
+// Neighbours are given as left, right, up and down.
+fn move_east(cell: &char, neighbours: &[char]) -> char {
+    match cell {
+        '.' if neighbours[0] == '>' => '>',
+        '>' if neighbours[1] == '.' => '.',
+        c => *c,
+    }
 }
 
-impl State {
-    fn advance(&self) -> Result<State, &str> {
-        let mut east: HashSet<(isize, isize)> = HashSet::new();
-        let mut south: HashSet<(isize, isize)> = HashSet::new();
-
-        for et in &self.east {
-            let new_et = ((et.0 + 1) % self.max_x, et.1);
-            if self.east.contains(&new_et) || self.south.contains(&new_et) {
-                east.insert(*et);
-            } else {
-                east.insert(new_et);
-            }
-        }
+fn move_south(cell: &char, neighbours: &[char]) -> char {
+    match cell {
+        '.' if neighbours[2] == 'v' => 'v',
+        'v' if neighbours[3] == '.' => '.',
+        c => *c,
+    }
+}
 
-        for st in &self.south {
-            let new_st = (st.0, -1 * (((-1 * st.1) + 1) % self.max_y));
-            if self.south.contains(&new_st) || east.contains(&new_st) {
-                south.insert(*st);
-            } else {
-                south.insert(new_st);
-            }
-        }
+/// The first step on which nothing moves, or `None` if the sea floor repeats itself before then,
+/// as a row of east-facing sea cucumbers with a gap in it keeps going round forever.
+fn settle(mut state: Automaton<char>) -> Option<usize> {
+    let mut counter: usize = 0;
+    let mut seen = HashSet::new();
 
-        let new_state = State {
-            east,
-            south,
-            max_x: self.max_x,
-            max_y: self.max_y,
-        };
-
-        if self == &new_state {
-            Err("No change in state")
-        } else {
-            Ok(new_state)
+    // The east-facing herd moves first, then the south-facing herd.
+    while seen.insert(state.rows()) {
+        counter += 1;
+        let moved = state.step(move_east) + state.step(move_south);
+        if moved == 0 {
+            return Some(counter);
         }
     }
+    None
+}
 
-    pub fn to_string(&self) -> String {
-        let mut output = String::new();
-        for y in 0..self.max_y {
-            let mut line = String::new();
-            for x in 0..self.max_x {
-                let tuple = (x, -1 * y);
-                if self.south.contains(&tuple) {
-                    line.push('v');
-                } else if self.east.contains(&tuple) {
-                    line.push('>');
-                } else {
-                    line.push('.');
-                }
-            }
-            line.push('\n');
-            output.push_str(&line);
-        }
-        output
+fn sea_floor(input: &Input) -> Automaton<char> {
+    let rows = input.lines().iter().map(|l| l.chars().collect()).collect();
+    let neighbourhood = Neighbourhood::Offsets(vec![(-1, 0), (1, 0), (0, -1), (0, 1)]);
+    Automaton::new(rows, neighbourhood, Boundary::Wrapping)
+}
+
+fn colour(cell: &char) -> (u8, u8, u8) {
+    match cell {
+        '>' => (230, 80, 40),
+        'v' => (40, 120, 230),
+        _ => (10, 20, 40),
     }
 }
 
-fn solve() -> usize {
-    let mut counter: usize = 0;
-    let mut state = get_state();
+/// Write the sea floor as it looks before every step, until the herds stop moving or the sea
+/// floor repeats itself.
+pub fn render(input: &str, directory: &Path) -> io::Result<()> {
+    let mut state = parse(input).map_err(invalid_input)?;
+    let mut frames = FrameWriter::new(directory, "day_25")?;
+    let mut seen = HashSet::new();
 
-    loop {
-        counter += 1;
-        match state.advance() {
-            Ok(s) => {
-                state = s;
-            }
-            Err(_) => {
-                return counter;
-            }
+    while seen.insert(state.rows()) {
+        let pixels: Vec<Vec<(u8, u8, u8)>> = state
+            .rows()
+            .iter()
+            .map(|r| r.iter().map(colour).collect())
+            .collect();
+        frames.write_ppm(&pixels)?;
+        if state.step(move_east) + state.step(move_south) == 0 {
+            break;
         }
     }
+    Ok(())
 }
 
-fn get_state() -> State {
-    let mut east = HashSet::new();
-    let mut south = HashSet::new();
-    for (i, line) in parse_lines("day_25".to_string()).iter().enumerate() {
-        for (j, c) in line.chars().enumerate() {
-            match c {
-                '>' => east.insert((j as isize, -1 * (i as isize))),
-                'v' => south.insert((j as isize, -1 * (i as isize))),
-                _ => false,
-            };
+/// Check that the sea floor is a rectangular grid of east and south facing sea cucumbers.
+pub fn validate(input: &str) -> Result<(), Vec<Diagnostic>> {
+    check_lines(input, |validator, lines| {
+        validator.grid(lines, 0);
+        for (i, line) in lines.iter().enumerate() {
+            validator.charset(i, line, ">v.");
         }
-    }
+    })
+}
 
-    let max_x = east.iter().chain(south.iter()).map(|t| t.0).max().unwrap() + 1;
-    let max_y = east
-        .iter()
-        .chain(south.iter())
-        .map(|t| t.1 * -1)
-        .max()
-        .unwrap()
-        + 1;
-
-    State {
-        east,
-        south,
-        max_x,
-        max_y,
-    }
+/// Generate a `size` by `size` sea floor, with about a third of it free.
+pub fn generate(size: usize, seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+    (0..size)
+        .map(|_| {
+            let row = (0..size).map(|_| *rng.choose(&['>', 'v', '.']));
+            format!("{}\n", row.collect::<String>())
+        })
+        .collect()
+}
+
+/// Parse the sea floor into an automaton that wraps around at the edges.
+pub fn parse(input: &str) -> Result<Automaton<char>, Vec<Diagnostic>> {
+    validate(input)?;
+    Ok(sea_floor(&Input::new(input)))
+}
+
+/// The first step on which no sea cucumber moves.
+pub fn solve(input: &str) -> Result<usize, Vec<Diagnostic>> {
+    settle(parse(input)?).ok_or_else(|| {
+        vec![Diagnostic {
+            line: None,
+            message: "the sea cucumbers never stop moving".to_string(),
+        }]
+    })
 }
 
 pub fn day_25() -> (usize, usize) {
-    (solve(), 0)
+    (solve(&read_file("day_25".to_string())).unwrap(), 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day_25::{render, solve};
+    use aoc::fuzz::check_solver;
+    use std::env;
+    use std::fs;
+
+    const EXAMPLE: &str = "v...>>.vv>\n.vv>>.vv..\n>>.>v>...v\n>>v>>.>.v.\nv>v.vv.v..\n\
+         >.>>..v...\n.vv..>.>v.\nv.v..>>v.v\n....v..v.>";
+
+    #[test]
+    fn example() {
+        assert_eq!(solve(EXAMPLE), Ok(58));
+    }
+
+    #[test]
+    fn herds_that_never_stop() {
+        assert_eq!(
+            solve(">.").unwrap_err()[0].message,
+            "the sea cucumbers never stop moving"
+        );
+    }
+
+    #[test]
+    fn renders_until_the_herds_stop() {
+        let directory = env::temp_dir().join("day_25_renders_until_the_herds_stop");
+        render(EXAMPLE, &directory).unwrap();
+        assert_eq!(fs::read_dir(&directory).unwrap().count(), 58);
+        let first = fs::read_to_string(directory.join("day_25_00000.ppm")).unwrap();
+        assert!(first.starts_with("P3\n10 9\n255\n"));
+        fs::remove_dir_all(&directory).unwrap();
+
+        // The sea floor repeats itself after two steps, which ends the frames as well.
+        let directory = env::temp_dir().join("day_25_renders_a_repeating_sea_floor");
+        render(">.", &directory).unwrap();
+        assert_eq!(fs::read_dir(&directory).unwrap().count(), 2);
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    // Inputs that once hung the solution.
+    const REGRESSIONS: &[&str] = &[">.", "v\n."];
+
+    #[test]
+    fn fuzz_solver() {
+        check_solver(25, 1000, &[EXAMPLE], REGRESSIONS, solve);
+    }
 }
@@ -1,3 +1,14 @@
+//! Solutions to the Advent of Code 2021 puzzles, one module per day.
+//!
+//! Every day exposes `parse`, which validates an input and turns it into the puzzle's types, and
+//! `solve`, which returns the answers or the problems found in the input. `day_XX` solves the
+//! puzzle input in `input/day_XX.txt`.
+//!
+//! ```
+//! let answers = days::day_01::solve("199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n");
+//! assert_eq!(answers, Ok((7, 5)));
+//! ```
+
 pub mod day_01;
 pub mod day_02;
 pub mod day_03;
@@ -23,3 +34,53 @@ pub mod day_22;
 pub mod day_23;
 pub mod day_24;
 pub mod day_25;
+
+#[cfg(test)]
+mod tests {
+    use aoc::validate::Diagnostic;
+
+    type Generate = fn(usize, u64) -> String;
+    type Validate = fn(&str) -> Result<(), Vec<Diagnostic>>;
+
+    #[test]
+    fn generated_inputs_are_valid() {
+        let days: [(Generate, Validate); 25] = [
+            (crate::day_01::generate, crate::day_01::validate),
+            (crate::day_02::generate, crate::day_02::validate),
+            (crate::day_03::generate, crate::day_03::validate),
+            (crate::day_04::generate, crate::day_04::validate),
+            (crate::day_05::generate, crate::day_05::validate),
+            (crate::day_06::generate, crate::day_06::validate),
+            (crate::day_07::generate, crate::day_07::validate),
+            (crate::day_08::generate, crate::day_08::validate),
+            (crate::day_09::generate, crate::day_09::validate),
+            (crate::day_10::generate, crate::day_10::validate),
+            (crate::day_11::generate, crate::day_11::validate),
+            (crate::day_12::generate, crate::day_12::validate),
+            (crate::day_13::generate, crate::day_13::validate),
+            (crate::day_14::generate, crate::day_14::validate),
+            (crate::day_15::generate, crate::day_15::validate),
+            (crate::day_16::generate, crate::day_16::validate),
+            (crate::day_17::generate, crate::day_17::validate),
+            (crate::day_18::generate, crate::day_18::validate),
+            (crate::day_19::generate, crate::day_19::validate),
+            (crate::day_20::generate, crate::day_20::validate),
+            (crate::day_21::generate, crate::day_21::validate),
+            (crate::day_22::generate, crate::day_22::validate),
+            (crate::day_23::generate, crate::day_23::validate),
+            (crate::day_24::generate, crate::day_24::validate),
+            (crate::day_25::generate, crate::day_25::validate),
+        ];
+        for (day, (generate, validate)) in days.iter().enumerate() {
+            for (size, seed) in [(1, 1), (10, 2), (50, 3)] {
+                let input = generate(size, seed);
+                let context = format!("day {} with size {} and seed {}", day + 1, size, seed);
+                assert_eq!(generate(size, seed), input, "{}", context);
+                assert_eq!(validate(&input), Ok(()), "{}", context);
+            }
+        }
+
+        let unfolded = crate::day_23::unfold(&crate::day_23::generate(1, 1));
+        assert_eq!(crate::day_23::validate(&unfolded), Ok(()));
+    }
+}
@@ -1,3 +1,4 @@
+use aoc::validate::Diagnostic;
 use days::day_01::day_01;
 use days::day_02::day_02;
 use days::day_03::day_03;
@@ -25,10 +26,127 @@ use days::day_24::day_24;
 use days::day_25::day_25;
 use std::collections::HashMap;
 use std::env;
+use std::fs;
+use std::io;
+use std::path::Path;
 use std::time::Instant;
 
+type Generate = fn(usize, u64) -> String;
+type Validate = fn(&str) -> Result<(), Vec<Diagnostic>>;
+type Render = fn(&str, &Path) -> io::Result<()>;
+
+/// Validate every input file of a day, returns the diagnostics prefixed with their file.
+fn validate(day: &str, validator: Validate) -> Vec<String> {
+    let files = if day == "23" {
+        vec!["day_23_a".to_string(), "day_23_b".to_string()]
+    } else {
+        vec![format!("day_{}", day)]
+    };
+
+    let mut messages = Vec::new();
+    for file in files {
+        let path = format!("input/{}.txt", file);
+        match fs::read_to_string(&path) {
+            Ok(input) => {
+                if let Err(diagnostics) = validator(&input) {
+                    messages.extend(diagnostics.iter().map(|d| format!("{}: {}", path, d)));
+                }
+            }
+            Err(error) => messages.push(format!("{}: {}", path, error)),
+        }
+    }
+    messages
+}
+
+fn render(args: &[String]) {
+    let mut renderers: HashMap<String, Render> = HashMap::new();
+    renderers.insert("05".to_string(), days::day_05::render);
+    renderers.insert("13".to_string(), days::day_13::render);
+    renderers.insert("20".to_string(), days::day_20::render);
+    renderers.insert("25".to_string(), days::day_25::render);
+
+    let day = args.first().expect("usage: render <day> [directory]");
+    let directory = Path::new(args.get(1).map_or("frames", |d| d.as_str()));
+    let renderer = renderers
+        .get(day)
+        .unwrap_or_else(|| panic!("no renderer for day {}", day));
+    let input = fs::read_to_string(format!("input/day_{}.txt", day)).expect("could not read input");
+    renderer(&input, directory).expect("could not write frames");
+    println!("Frames written to {}", directory.display());
+}
+
+/// Write a generated input to `<directory>/input`, so that the days can be run on it from
+/// `<directory>`.
+fn generate(args: &[String]) {
+    let mut generators: HashMap<String, Generate> = HashMap::new();
+    generators.insert("01".to_string(), days::day_01::generate);
+    generators.insert("02".to_string(), days::day_02::generate);
+    generators.insert("03".to_string(), days::day_03::generate);
+    generators.insert("04".to_string(), days::day_04::generate);
+    generators.insert("05".to_string(), days::day_05::generate);
+    generators.insert("06".to_string(), days::day_06::generate);
+    generators.insert("07".to_string(), days::day_07::generate);
+    generators.insert("08".to_string(), days::day_08::generate);
+    generators.insert("09".to_string(), days::day_09::generate);
+    generators.insert("10".to_string(), days::day_10::generate);
+    generators.insert("11".to_string(), days::day_11::generate);
+    generators.insert("12".to_string(), days::day_12::generate);
+    generators.insert("13".to_string(), days::day_13::generate);
+    generators.insert("14".to_string(), days::day_14::generate);
+    generators.insert("15".to_string(), days::day_15::generate);
+    generators.insert("16".to_string(), days::day_16::generate);
+    generators.insert("17".to_string(), days::day_17::generate);
+    generators.insert("18".to_string(), days::day_18::generate);
+    generators.insert("19".to_string(), days::day_19::generate);
+    generators.insert("20".to_string(), days::day_20::generate);
+    generators.insert("21".to_string(), days::day_21::generate);
+    generators.insert("22".to_string(), days::day_22::generate);
+    generators.insert("23".to_string(), days::day_23::generate);
+    generators.insert("24".to_string(), days::day_24::generate);
+    generators.insert("25".to_string(), days::day_25::generate);
+
+    let usage = "usage: generate <day> <size> [seed] [directory]";
+    let day = args.first().expect(usage);
+    let size = args
+        .get(1)
+        .and_then(|s| s.parse::<usize>().ok())
+        .filter(|s| *s > 0)
+        .expect(usage);
+    let seed = args
+        .get(2)
+        .map_or(Ok(2021), |s| s.parse::<u64>())
+        .expect(usage);
+    let directory = Path::new(args.get(3).map_or("generated", |d| d.as_str())).join("input");
+    let input = match generators.get(day) {
+        Some(f) => f(size, seed),
+        None => panic!("no generator for day {}", day),
+    };
+
+    let files = if day == "23" {
+        let unfolded = days::day_23::unfold(&input);
+        vec![
+            ("day_23_a".to_string(), input),
+            ("day_23_b".to_string(), unfolded),
+        ]
+    } else {
+        vec![(format!("day_{}", day), input)]
+    };
+    fs::create_dir_all(&directory).expect("could not create the directory");
+    for (file, input) in files {
+        let path = directory.join(format!("{}.txt", file));
+        fs::write(&path, input).expect("could not write the input");
+        println!("Input written to {}", path.display());
+    }
+}
+
 fn main() {
     let args: Vec<String> = env::args().collect();
+    if args.get(1).is_some_and(|a| a == "render") {
+        return render(&args[2..]);
+    }
+    if args.get(1).is_some_and(|a| a == "generate") {
+        return generate(&args[2..]);
+    }
 
     let mut methods: HashMap<String, fn() -> (usize, usize)> = HashMap::new();
     methods.insert("01".to_string(), day_01);
@@ -57,6 +175,33 @@ fn main() {
     methods.insert("24".to_string(), day_24);
     methods.insert("25".to_string(), day_25);
 
+    let mut validators: HashMap<String, Validate> = HashMap::new();
+    validators.insert("01".to_string(), days::day_01::validate);
+    validators.insert("02".to_string(), days::day_02::validate);
+    validators.insert("03".to_string(), days::day_03::validate);
+    validators.insert("04".to_string(), days::day_04::validate);
+    validators.insert("05".to_string(), days::day_05::validate);
+    validators.insert("06".to_string(), days::day_06::validate);
+    validators.insert("07".to_string(), days::day_07::validate);
+    validators.insert("08".to_string(), days::day_08::validate);
+    validators.insert("09".to_string(), days::day_09::validate);
+    validators.insert("10".to_string(), days::day_10::validate);
+    validators.insert("11".to_string(), days::day_11::validate);
+    validators.insert("12".to_string(), days::day_12::validate);
+    validators.insert("13".to_string(), days::day_13::validate);
+    validators.insert("14".to_string(), days::day_14::validate);
+    validators.insert("15".to_string(), days::day_15::validate);
+    validators.insert("16".to_string(), days::day_16::validate);
+    validators.insert("17".to_string(), days::day_17::validate);
+    validators.insert("18".to_string(), days::day_18::validate);
+    validators.insert("19".to_string(), days::day_19::validate);
+    validators.insert("20".to_string(), days::day_20::validate);
+    validators.insert("21".to_string(), days::day_21::validate);
+    validators.insert("22".to_string(), days::day_22::validate);
+    validators.insert("23".to_string(), days::day_23::validate);
+    validators.insert("24".to_string(), days::day_24::validate);
+    validators.insert("25".to_string(), days::day_25::validate);
+
     let mut days = args.iter().collect::<Vec<&String>>();
     days.remove(0);
     if days.is_empty() {
@@ -69,6 +214,18 @@ fn main() {
         "Day", "Part A", "Part B", "Runtime"
     );
     for day in days {
+        let diagnostics = match validators.get(day) {
+            Some(v) => validate(day, *v),
+            None => panic!("unknown day"),
+        };
+        if !diagnostics.is_empty() {
+            println!("{0: <4} | invalid input", day);
+            for diagnostic in diagnostics {
+                println!("     | {}", diagnostic);
+            }
+            continue;
+        }
+
         let now = Instant::now();
         let t = match methods.get(day) {
             Some(f) => f(),